@@ -0,0 +1,134 @@
+//! Synthetic ELF binaries with a Tauri-like asset table, for benches.
+
+use std::io::Write;
+
+const BASE: u64 = 0x400000;
+const RODATA_OFFSET: usize = 0x100;
+
+/// A statically linked x86_64 ELF whose `.rodata` holds `noise_len` bytes of pseudo-random
+/// data followed by the brotli compressed `assets` and the table pointing at them.
+pub struct Fixture {
+    pub binary: Vec<u8>,
+    /// File offset of the first asset table entry.
+    pub table_offset: usize,
+    /// Size of `.rodata`, i.e. of what the scan walks through.
+    pub rodata_len: usize,
+}
+
+pub fn synthetic_elf(noise_len: usize, assets: &[(&str, &[u8])]) -> Fixture {
+    let mut rodata = noise(noise_len);
+
+    let mut entries = Vec::new();
+    for (name, content) in assets {
+        let name_offset = rodata.len();
+        rodata.extend_from_slice(name.as_bytes());
+        let data_offset = rodata.len();
+        let data = compress(content);
+        rodata.extend_from_slice(&data);
+        entries.push((name_offset, name.len(), data_offset, data.len()));
+    }
+
+    while !rodata.len().is_multiple_of(8) {
+        rodata.push(0);
+    }
+    let table_offset = RODATA_OFFSET + rodata.len();
+    let vaddr = |offset: usize| BASE + (RODATA_OFFSET + offset) as u64;
+    for (name_offset, name_len, data_offset, data_len) in entries {
+        rodata.extend_from_slice(&vaddr(name_offset).to_le_bytes());
+        rodata.extend_from_slice(&(name_len as u64).to_le_bytes());
+        rodata.extend_from_slice(&vaddr(data_offset).to_le_bytes());
+        rodata.extend_from_slice(&(data_len as u64).to_le_bytes());
+    }
+
+    Fixture {
+        rodata_len: rodata.len(),
+        binary: elf(&rodata),
+        table_offset,
+    }
+}
+
+fn elf(rodata: &[u8]) -> Vec<u8> {
+    let shstrtab = b"\0.rodata\0.shstrtab\0";
+    let shstrtab_offset = RODATA_OFFSET + rodata.len();
+    let shoff = (shstrtab_offset + shstrtab.len()).next_multiple_of(8);
+    let file_len = shoff + 3 * 64;
+
+    let mut out = Vec::with_capacity(file_len);
+    // ELF header
+    out.extend_from_slice(b"\x7fELF\x02\x01\x01\0\0\0\0\0\0\0\0\0");
+    out.extend_from_slice(&2u16.to_le_bytes()); // ET_EXEC
+    out.extend_from_slice(&62u16.to_le_bytes()); // EM_X86_64
+    out.extend_from_slice(&1u32.to_le_bytes());
+    out.extend_from_slice(&BASE.to_le_bytes()); // entry
+    out.extend_from_slice(&64u64.to_le_bytes()); // phoff
+    out.extend_from_slice(&(shoff as u64).to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    for half in [64u16, 56, 1, 64, 3, 2] {
+        out.extend_from_slice(&half.to_le_bytes());
+    }
+    // PT_LOAD mapping the whole file
+    out.extend_from_slice(&1u32.to_le_bytes());
+    out.extend_from_slice(&4u32.to_le_bytes()); // PF_R
+    for word in [0, BASE, BASE, file_len as u64, file_len as u64, 0x1000] {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+    out.resize(RODATA_OFFSET, 0);
+    out.extend_from_slice(rodata);
+    out.extend_from_slice(shstrtab);
+    out.resize(shoff, 0);
+
+    // section headers: null, .rodata, .shstrtab
+    out.extend_from_slice(&[0; 64]);
+    section_header(
+        &mut out,
+        1,
+        1,
+        2,
+        BASE + RODATA_OFFSET as u64,
+        RODATA_OFFSET,
+        rodata.len(),
+    );
+    section_header(&mut out, 9, 3, 0, 0, shstrtab_offset, shstrtab.len());
+    out
+}
+
+fn section_header(
+    out: &mut Vec<u8>,
+    name: u32,
+    kind: u32,
+    flags: u64,
+    addr: u64,
+    offset: usize,
+    size: usize,
+) {
+    out.extend_from_slice(&name.to_le_bytes());
+    out.extend_from_slice(&kind.to_le_bytes());
+    for word in [flags, addr, offset as u64, size as u64] {
+        out.extend_from_slice(&word.to_le_bytes());
+    }
+    out.extend_from_slice(&[0; 8]); // link, info
+    out.extend_from_slice(&8u64.to_le_bytes());
+    out.extend_from_slice(&0u64.to_le_bytes());
+}
+
+// xorshift, so every run scans the same bytes
+fn noise(len: usize) -> Vec<u8> {
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut out = Vec::with_capacity(len + 8);
+    while out.len() < len {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        out.extend_from_slice(&state.to_le_bytes());
+    }
+    out.truncate(len);
+    out
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut writer = brotli::CompressorWriter::new(&mut out, 4096, 9, 22);
+    writer.write_all(data).unwrap();
+    drop(writer);
+    out
+}
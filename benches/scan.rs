@@ -0,0 +1,46 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use std::hint::black_box;
+use tauri_dumper::Dumper;
+
+mod common;
+
+const INDEX: &[u8] =
+    b"<!doctype html><html><head><script src=\"/assets/app.js\"></script></head></html>";
+const APP: &[u8] = b"console.log('hello from the synthetic fixture');";
+
+fn scan(c: &mut Criterion) {
+    let mut group = c.benchmark_group("heuristic_search_assets");
+    for mib in [1, 8] {
+        let fixture = common::synthetic_elf(
+            mib << 20,
+            &[("/index.html", INDEX), ("/assets/app.js", APP)],
+        );
+        let dumper = Dumper::from_bytes(fixture.binary).unwrap();
+        assert_eq!(dumper.heuristic_search_assets().unwrap().len(), 2);
+
+        group.throughput(Throughput::Bytes(fixture.rodata_len as u64));
+        group.bench_function(format!("{mib}MiB"), |b| {
+            b.iter(|| dumper.heuristic_search_assets().unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn validate(c: &mut Criterion) {
+    let fixture = common::synthetic_elf(4096, &[("/index.html", INDEX)]);
+    let table_offset = fixture.table_offset;
+    let dumper = Dumper::from_bytes(fixture.binary).unwrap();
+
+    let mut group = c.benchmark_group("asset_at");
+    group.bench_function("valid", |b| {
+        b.iter(|| dumper.asset_at(black_box(table_offset)).unwrap())
+    });
+    // pointer-like noise, what nearly every scan candidate looks like
+    group.bench_function("noise", |b| {
+        b.iter(|| dumper.asset_at(black_box(0x200)).unwrap_err())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, scan, validate);
+criterion_main!(benches);
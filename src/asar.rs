@@ -0,0 +1,111 @@
+use anyhow::{anyhow, Context, Result};
+use memchr::memmem;
+use serde_json::Value;
+
+// every ASAR directory is a JSON object opening with its file list
+const HEADER_START: &[u8] = b"{\"files\":";
+
+// a file packed into an ASAR archive, `offset` is into the searched data
+#[derive(Debug)]
+pub(crate) struct AsarFile {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// Finds the ASAR archives (Electron's `app.asar`) embedded anywhere in `data` and lists the
+/// files packed into them.
+pub(crate) fn asar_files(data: &[u8]) -> Vec<AsarFile> {
+    let mut files = Vec::new();
+    for json_start in memmem::find_iter(data, HEADER_START) {
+        match parse_archive(data, json_start) {
+            Ok(archive) => files.extend(archive),
+            Err(e) => log::debug!("{:#x}: not an ASAR header, {:#}", json_start, e),
+        }
+    }
+    files
+}
+
+// the archive starts with two pickles: the header size, then the JSON directory itself
+//   u32 4, u32 header size, u32 payload size, u32 JSON length, JSON, padding, file contents
+fn parse_archive(data: &[u8], json_start: usize) -> Result<Vec<AsarFile>> {
+    let archive_start = json_start
+        .checked_sub(16)
+        .context("no room for the sizes")?;
+    let word = |i: usize| {
+        let at = archive_start + 4 * i;
+        u32::from_le_bytes(data[at..at + 4].try_into().unwrap()) as usize
+    };
+    let (size_pickle, header_size, payload_size, json_len) = (word(0), word(1), word(2), word(3));
+    // the sizes are read from arbitrary data, they may be anything up to u32::MAX
+    let plausible = size_pickle == 4
+        && payload_size.checked_add(4) == Some(header_size)
+        && json_len
+            .checked_add(4)
+            .is_some_and(|len| len <= payload_size);
+    if !plausible {
+        return Err(anyhow!("implausible sizes"));
+    }
+    let json = json_start
+        .checked_add(json_len)
+        .and_then(|end| data.get(json_start..end))
+        .context("directory runs past the end of the data")?;
+    let directory: Value = serde_json::from_slice(json).context("directory is not JSON")?;
+
+    let mut files = Vec::new();
+    let base = (archive_start + 8)
+        .checked_add(header_size)
+        .context("files start past the end of the address space")?;
+    collect(&directory, "", base, data.len(), &mut files)?;
+    log::debug!(
+        "{:#x}: ASAR archive, {} files from {:#x}",
+        archive_start,
+        files.len(),
+        base
+    );
+    Ok(files)
+}
+
+fn collect(
+    directory: &Value,
+    prefix: &str,
+    base: usize,
+    len: usize,
+    files: &mut Vec<AsarFile>,
+) -> Result<()> {
+    let entries = directory
+        .get("files")
+        .and_then(Value::as_object)
+        .context("directory has no file list")?;
+    for (name, entry) in entries {
+        let name = format!("{}/{}", prefix, name);
+        if entry.get("files").is_some() {
+            collect(entry, &name, base, len, files)?;
+            continue;
+        }
+        // unpacked files live next to the archive, links point at other entries
+        if entry.get("unpacked").and_then(Value::as_bool) == Some(true)
+            || entry.get("link").is_some()
+        {
+            log::debug!("{}: not stored in the archive", name);
+            continue;
+        }
+
+        let size = entry
+            .get("size")
+            .and_then(Value::as_u64)
+            .with_context(|| format!("{}: no size", name))? as usize;
+        // offsets are strings, they may exceed what a JSON number holds exactly
+        let offset = entry
+            .get("offset")
+            .and_then(Value::as_str)
+            .and_then(|offset| offset.parse::<usize>().ok())
+            .with_context(|| format!("{}: no offset", name))?;
+        let offset = base
+            .checked_add(offset)
+            .filter(|offset| offset.saturating_add(size) <= len)
+            .with_context(|| format!("{}: data is out of range", name))?;
+        files.push(AsarFile { name, offset, size });
+    }
+    Ok(())
+}
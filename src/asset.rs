@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Codec;
+
+/// An asset recovered from the binary, as stored in it (usually brotli compressed).
+#[derive(Debug)]
+pub struct Asset {
+    /// Path the asset is served under, e.g. `/index.html`.
+    pub name: String,
+    /// Compressed asset bytes.
+    pub data: Vec<u8>,
+    /// Offset of `data` in the binary.
+    pub data_offset: u64,
+    /// Where the asset table lists the asset, `None` for PE resources and ASAR files.
+    pub table_entry: Option<TableEntry>,
+    /// Codec `data` is encoded with.
+    pub codec: Codec,
+    /// Content decoded while validating the asset, reused by
+    /// [`Dumper::decompress_asset`](crate::Dumper::decompress_asset). `None` past
+    /// [`ScanOptions::max_kept_decoded`](crate::ScanOptions::max_kept_decoded).
+    pub decompressed: Option<Vec<u8>>,
+}
+
+/// Location of an asset table entry, to look it up in a disassembler.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableEntry {
+    /// Offset of the entry in the binary.
+    pub offset: u64,
+    /// Virtual address of the name, as the entry (or its relocation) holds it.
+    pub name_address: u64,
+    /// Virtual address of the data, as the entry (or its relocation) holds it.
+    pub data_address: u64,
+}
+
+impl Asset {
+    /// Size of the decoded content, `None` when it was not decoded during the scan (e.g. with
+    /// [`ScanOptions::validate_data`](crate::ScanOptions::validate_data) off) or not kept.
+    pub fn decompressed_len(&self) -> Option<usize> {
+        match (&self.decompressed, self.codec) {
+            (Some(decompressed), _) => Some(decompressed.len()),
+            (None, Codec::Identity) => Some(self.data.len()),
+            (None, _) => None,
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use brotli::enc::StandardAlloc;
+use brotli::{BrotliDecompressStream, BrotliResult, BrotliState};
+use flate2::bufread::GzDecoder;
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+use std::io::{Read, Write};
+
+use crate::error::{DumperError, Result};
+
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
+
+/// Default size of the buffer brotli decodes into, see [`ScanOptions::brotli_buffer_size`].
+///
+/// [`ScanOptions::brotli_buffer_size`]: crate::ScanOptions::brotli_buffer_size
+pub const DEFAULT_BROTLI_BUFFER_SIZE: usize = 64 * 1024;
+
+// how brotli streams are decoded, see `ScanOptions`
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BrotliOptions<'a> {
+    pub buffer_size: usize,
+    // custom dictionary the streams were compressed against, empty for none
+    pub dictionary: &'a [u8],
+}
+
+impl BrotliOptions<'_> {
+    const DEFAULT: Self = Self {
+        buffer_size: DEFAULT_BROTLI_BUFFER_SIZE,
+        dictionary: &[],
+    };
+}
+
+/// A compression scheme the built-in codecs do not cover, plugged in as a [`Codec::Custom`].
+///
+/// ```
+/// use std::error::Error;
+/// use tauri_dumper::{Codec, Decoder, ScanOptions};
+///
+/// #[derive(Debug)]
+/// struct Reversed;
+///
+/// impl Decoder for Reversed {
+///     fn name(&self) -> &'static str {
+///         "reversed"
+///     }
+///
+///     fn try_decode(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>> {
+///         Ok(input.iter().rev().copied().collect())
+///     }
+/// }
+///
+/// let mut options = ScanOptions::default();
+/// options.codecs.insert(0, Codec::Custom(&Reversed));
+/// ```
+pub trait Decoder: fmt::Debug + Send + Sync {
+    /// Short name, shown in listings and manifests.
+    fn name(&self) -> &'static str;
+
+    /// File extension conventionally used for the scheme, `None` if there is none.
+    fn extension(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Cheap check of the leading bytes, e.g. for a magic number, before decoding anything.
+    fn sniff(&self, input: &[u8]) -> bool {
+        !input.is_empty()
+    }
+
+    /// Decodes `input`, failing if it is not a valid stream.
+    fn try_decode(&self, input: &[u8]) -> Result<Vec<u8>, Box<dyn Error + Send + Sync>>;
+}
+
+/// Encoding of the asset bytes embedded in the binary.
+#[derive(Clone, Copy)]
+pub enum Codec {
+    /// Brotli, what Tauri uses by default.
+    Brotli,
+    Gzip,
+    Zstd,
+    /// Stored as-is, e.g. when the `compression` feature of Tauri is disabled.
+    Identity,
+    /// A scheme implemented outside the crate.
+    Custom(&'static dyn Decoder),
+}
+
+impl Codec {
+    /// The built-in codecs, in the order auto-detection tries them.
+    pub const BUILTIN: [Codec; 4] = [Self::Brotli, Self::Gzip, Self::Zstd, Self::Identity];
+
+    /// Guesses the codec of `data`, trying brotli first and falling back to gzip, zstd and
+    /// raw bytes.
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        Self::detect_decoded(data).map(|(codec, _)| codec)
+    }
+
+    /// Like [`Codec::detect`], but also hands back the content decoded along the way.
+    pub fn detect_decoded(data: &[u8]) -> Option<(Self, Cow<'_, [u8]>)> {
+        Self::detect_decoded_in(&Self::BUILTIN, data, BrotliOptions::DEFAULT)
+    }
+
+    // tries `codecs` in turn, the first one `data` decodes with wins
+    pub(crate) fn detect_decoded_in<'a>(
+        codecs: &[Codec],
+        data: &'a [u8],
+        brotli: BrotliOptions,
+    ) -> Option<(Self, Cow<'a, [u8]>)> {
+        codecs
+            .iter()
+            .find_map(|&codec| Some((codec, codec.probe_in(data, brotli)?)))
+    }
+
+    /// Guesses the codec of `data` from its leading bytes alone, without decoding anything.
+    ///
+    /// Much cheaper than [`Codec::detect`], but brotli has no magic number, so most data
+    /// that is neither gzip nor zstd passes as brotli.
+    pub fn sniff(data: &[u8]) -> Option<Self> {
+        Self::sniff_in(&Self::BUILTIN, data)
+    }
+
+    pub(crate) fn sniff_in(codecs: &[Codec], data: &[u8]) -> Option<Self> {
+        // brotli and raw bytes accept nearly anything, they only fill in for the rest
+        let weak = |codec: &&Codec| matches!(codec, Self::Brotli | Self::Identity);
+        codecs
+            .iter()
+            .filter(|codec| !weak(codec))
+            .chain(codecs.iter().filter(weak))
+            .find(|codec| codec.plausible(data))
+            .copied()
+    }
+
+    // the cheap header checks run before decoding
+    fn plausible(&self, data: &[u8]) -> bool {
+        match self {
+            Self::Brotli => brotli_header_ok(data),
+            Self::Gzip => data.starts_with(&GZIP_MAGIC),
+            Self::Zstd => data.starts_with(&ZSTD_MAGIC),
+            // an empty blob would turn every pointer-like candidate into an asset
+            Self::Identity => !data.is_empty(),
+            Self::Custom(decoder) => decoder.sniff(data),
+        }
+    }
+
+    /// Checks `data` is a valid stream for this codec and returns its decoded content.
+    ///
+    /// Cheap header checks run first, so most garbage is turned down without decoding it.
+    pub fn probe<'a>(&self, data: &'a [u8]) -> Option<Cow<'a, [u8]>> {
+        self.probe_in(data, BrotliOptions::DEFAULT)
+    }
+
+    pub(crate) fn probe_in<'a>(
+        &self,
+        data: &'a [u8],
+        brotli: BrotliOptions,
+    ) -> Option<Cow<'a, [u8]>> {
+        if !self.plausible(data) {
+            return None;
+        }
+        match self {
+            Self::Identity => Some(Cow::Borrowed(data)),
+            _ => self.decode_in(data, brotli).ok().map(Cow::Owned),
+        }
+    }
+
+    // like `probe_in`, decoding into `out` rather than into memory, raw bytes are not copied
+    pub(crate) fn probe_into(
+        &self,
+        data: &[u8],
+        out: &mut dyn Write,
+        brotli: BrotliOptions,
+    ) -> bool {
+        if !self.plausible(data) {
+            return false;
+        }
+        match self {
+            Self::Identity => true,
+            _ => self.decode_into(data, out, brotli).is_ok(),
+        }
+    }
+
+    /// File extension conventionally used for this codec, `None` for raw bytes.
+    pub fn extension(&self) -> Option<&'static str> {
+        match self {
+            Self::Brotli => Some("br"),
+            Self::Gzip => Some("gz"),
+            Self::Zstd => Some("zst"),
+            Self::Identity => None,
+            Self::Custom(decoder) => decoder.extension(),
+        }
+    }
+
+    /// Decodes `data` with this codec.
+    ///
+    /// The asset table carries no decompressed size, but compressed streams are
+    /// self-terminating: a stream that ends early is reported as an error, never truncated.
+    /// Streams concatenated back to back are decoded in turn, and whatever follows the last
+    /// one, e.g. padding up to the declared size, is ignored.
+    pub fn decode(&self, data: &[u8]) -> Result<Vec<u8>> {
+        self.decode_with(data, DEFAULT_BROTLI_BUFFER_SIZE)
+    }
+
+    /// Like [`Codec::decode`], with brotli decoding through a `buffer_size` bytes buffer.
+    pub fn decode_with(&self, data: &[u8], buffer_size: usize) -> Result<Vec<u8>> {
+        self.decode_in(
+            data,
+            BrotliOptions {
+                buffer_size,
+                ..BrotliOptions::DEFAULT
+            },
+        )
+    }
+
+    pub(crate) fn decode_in(&self, data: &[u8], brotli: BrotliOptions) -> Result<Vec<u8>> {
+        if let Self::Custom(decoder) = self {
+            return decoder
+                .try_decode(data)
+                .map_err(|e| DumperError::decompression(*self, e));
+        }
+        let mut decompressed = Vec::new();
+        self.decode_into(data, &mut decompressed, brotli)?;
+        Ok(decompressed)
+    }
+
+    /// Like [`Codec::decode`], writing the content to `out` as it is decoded rather than
+    /// collecting it. On error `out` may already hold part of the content.
+    pub fn decode_to(&self, data: &[u8], mut out: impl Write) -> Result<()> {
+        self.decode_into(data, &mut out, BrotliOptions::DEFAULT)
+    }
+
+    pub(crate) fn decode_into(
+        &self,
+        data: &[u8],
+        out: &mut dyn Write,
+        brotli: BrotliOptions,
+    ) -> Result<()> {
+        if let Self::Custom(_) = self {
+            out.write_all(&self.decode_in(data, brotli)?)?;
+            return Ok(());
+        }
+        let consumed = self.decode_stream(data, out, brotli)?;
+
+        // only the first stream has to be valid, trailing bytes count when they decode, so
+        // they are held back until then
+        let mut rest = &data[consumed..];
+        while self.starts_stream(rest) {
+            let mut next = Vec::new();
+            match self.decode_stream(rest, &mut next, brotli) {
+                Ok(consumed) if consumed > 0 => {
+                    out.write_all(&next)?;
+                    rest = &rest[consumed..];
+                }
+                _ => break,
+            }
+        }
+        Ok(())
+    }
+
+    // decodes the stream at the start of `data` into `out`, returns the bytes it spans
+    fn decode_stream(
+        &self,
+        data: &[u8],
+        out: &mut dyn Write,
+        brotli: BrotliOptions,
+    ) -> Result<usize> {
+        match self {
+            Self::Brotli => decode_brotli_stream(data, out, brotli),
+            Self::Gzip => {
+                let mut decoder = GzDecoder::new(data);
+                self.copy_decoded(&mut decoder, out)?;
+                Ok(data.len() - decoder.into_inner().len())
+            }
+            Self::Zstd => {
+                let mut input = data;
+                let mut decoder = ruzstd::StreamingDecoder::new(&mut input)
+                    .map_err(|e| DumperError::decompression(*self, e))?;
+                self.copy_decoded(&mut decoder, out)?;
+                Ok(data.len() - input.len())
+            }
+            Self::Identity => {
+                out.write_all(data)?;
+                Ok(data.len())
+            }
+            Self::Custom(_) => {
+                out.write_all(&self.decode_in(data, brotli)?)?;
+                Ok(data.len())
+            }
+        }
+    }
+
+    // like `io::copy`, telling the errors of the decoder from those of `out`
+    fn copy_decoded(&self, decoder: &mut impl Read, out: &mut dyn Write) -> Result<()> {
+        const BUFFER_SIZE: usize = 64 * 1024;
+
+        let mut buffer = vec![0; BUFFER_SIZE];
+        loop {
+            match decoder.read(&mut buffer) {
+                Ok(0) => return Ok(()),
+                Ok(n) => out.write_all(&buffer[..n])?,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(DumperError::decompression(*self, e)),
+            }
+        }
+    }
+
+    fn starts_stream(&self, data: &[u8]) -> bool {
+        match self {
+            // zero padding would parse as the start of a brotli stream
+            Self::Brotli => data.iter().any(|&b| b != 0) && brotli_header_ok(data),
+            Self::Gzip => data.starts_with(&GZIP_MAGIC),
+            Self::Zstd => data.starts_with(&ZSTD_MAGIC),
+            Self::Identity | Self::Custom(_) => false,
+        }
+    }
+}
+
+// unlike `brotli::Decompressor`, the low-level API tells where the stream ends
+fn decode_brotli_stream(data: &[u8], out: &mut dyn Write, brotli: BrotliOptions) -> Result<usize> {
+    let mut state = if brotli.dictionary.is_empty() {
+        BrotliState::new(
+            StandardAlloc::default(),
+            StandardAlloc::default(),
+            StandardAlloc::default(),
+        )
+    } else {
+        BrotliState::new_with_custom_dictionary(
+            StandardAlloc::default(),
+            StandardAlloc::default(),
+            StandardAlloc::default(),
+            brotli.dictionary.to_vec().into(),
+        )
+    };
+    let mut buffer = vec![0; brotli.buffer_size.max(1)];
+    let mut available_in = data.len();
+    let mut input_offset = 0;
+    loop {
+        let mut available_out = buffer.len();
+        let mut output_offset = 0;
+        let mut total_out = 0;
+        let result = BrotliDecompressStream(
+            &mut available_in,
+            &mut input_offset,
+            data,
+            &mut available_out,
+            &mut output_offset,
+            &mut buffer,
+            &mut total_out,
+            &mut state,
+        );
+        out.write_all(&buffer[..output_offset])?;
+        match result {
+            BrotliResult::ResultSuccess => return Ok(input_offset),
+            BrotliResult::NeedsMoreOutput => continue,
+            BrotliResult::NeedsMoreInput => {
+                return Err(DumperError::decompression(
+                    Codec::Brotli,
+                    "truncated stream",
+                ))
+            }
+            BrotliResult::ResultFailure => {
+                return Err(DumperError::decompression(Codec::Brotli, "invalid stream"))
+            }
+        }
+    }
+}
+
+// rejects streams whose window size (the first bits of every brotli stream) is reserved
+fn brotli_header_ok(data: &[u8]) -> bool {
+    let Some(&first) = data.first() else {
+        return false;
+    };
+    // WBITS, read from the low bits up: 0 -> 16, 1 then n != 0 -> 17 + n, 1 then 0 then m,
+    // where m == 1 is the reserved large window
+    first & 0x7F != 0x11
+}
+
+impl Codec {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Brotli => "brotli",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Identity => "raw",
+            Self::Custom(decoder) => decoder.name(),
+        }
+    }
+}
+
+impl fmt::Display for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl fmt::Debug for Codec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Brotli => f.write_str("Brotli"),
+            Self::Gzip => f.write_str("Gzip"),
+            Self::Zstd => f.write_str("Zstd"),
+            Self::Identity => f.write_str("Identity"),
+            Self::Custom(decoder) => f.debug_tuple("Custom").field(decoder).finish(),
+        }
+    }
+}
+
+// custom codecs are told apart by name
+impl PartialEq for Codec {
+    fn eq(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other) && self.name() == other.name()
+    }
+}
+
+impl Eq for Codec {}
@@ -0,0 +1,1836 @@
+use anyhow::{anyhow, Context};
+#[cfg(not(target_arch = "wasm32"))]
+use memmap2::Mmap;
+use object::read::elf::{ElfFile32, ElfFile64};
+use object::read::macho::{FatArch, MachOFatFile32, MachOFatFile64, MachOFile32, MachOFile64};
+use object::read::pe::{PeFile32, PeFile64};
+use object::{
+    elf, pe, Architecture, BinaryFormat, FileKind, Object, ObjectSection, ObjectSegment,
+    RelocationFlags, SectionFlags, SectionKind,
+};
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+use std::io::Write;
+use std::ops::{ControlFlow, Range};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::asar;
+use crate::codec::BrotliOptions;
+use crate::error::{DumperError, Result};
+use crate::fixups;
+use crate::resource::{self, Resource};
+use crate::version::{self, TauriVersion};
+use crate::{
+    Asset, AssetFormat, Codec, HeaderLayout, NameCollision, ScanOptions, Section, TableEntry,
+};
+
+// how often (in scanned bytes) `AssetIter::on_progress` callbacks fire
+const PROGRESS_INTERVAL: u64 = 64 * 1024;
+
+// start offsets per task of the parallel scan
+const SCAN_CHUNK: usize = 1024 * 1024;
+
+// misread entries that happen to pair a name with the data of its neighbour pass validation, so
+// a wrong field order finds a few assets rather than none
+const FEW_ASSETS: usize = 8;
+
+// longer names are far more likely to be misread lengths than real asset paths
+const MAX_NAME_LEN: u64 = 4096;
+
+// decoded asset table entry, fields are pointer-sized in the binary
+#[derive(Debug)]
+struct AssetHeader {
+    name_ptr: u64,
+    name_len: u64,
+    data_ptr: u64,
+    data_size: u64,
+}
+
+// `count` table entries from `start` on, `step` bytes apart
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    start: usize,
+    step: usize,
+    count: usize,
+}
+
+impl Run {
+    fn offsets(&self) -> impl Iterator<Item = usize> {
+        let Run { start, step, count } = *self;
+        (0..count).map(move |i| start + i * step)
+    }
+}
+
+// a table entry whose pointers were translated to these file ranges
+#[derive(Debug)]
+struct ResolvedEntry {
+    table_entry: TableEntry,
+    name: Range<usize>,
+    data: Range<usize>,
+}
+
+// why a candidate table entry is not an asset
+#[derive(Debug, thiserror::Error)]
+enum RejectReason {
+    #[error("entry at {0:#x} runs past the end of the file")]
+    EntryOutOfRange(usize),
+    #[error("name pointer {0:#x} maps to no file offset")]
+    UnmappedName(u64),
+    #[error("data pointer {0:#x} maps to no file offset")]
+    UnmappedData(u64),
+    #[error("name length {0:#x} is implausible")]
+    NameLength(u64),
+    #[error("data size {0:#x} exceeds the maximum")]
+    DataTooLarge(u64),
+    #[error("name {ptr:#x}+{len:#x} is out of range")]
+    NameOutOfRange { ptr: u64, len: u64 },
+    #[error("data {ptr:#x}+{len:#x} is out of range")]
+    DataOutOfRange { ptr: u64, len: u64 },
+    #[error("name at {name:#x} and data at {data:#x} overlap")]
+    Overlap { name: usize, data: usize },
+    #[error("name does not start with {0:?}")]
+    NamePrefix(String),
+    #[error("name does not start like a path")]
+    NameNotPath,
+    #[error("name is not UTF-8")]
+    NameNotUtf8,
+    #[error("name contains {0:?}")]
+    NameChar(char),
+    #[error("data is not valid {0}")]
+    Undecodable(Codec),
+    #[error("data matches no known codec")]
+    UnknownCodec,
+}
+
+// decoded content up to `limit` bytes, past that it is dropped and only the decoding goes on
+struct Capped {
+    limit: usize,
+    content: Option<Vec<u8>>,
+}
+
+impl Write for Capped {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Some(content) = &mut self.content {
+            if content.len().saturating_add(buf.len()) > self.limit {
+                self.content = None;
+            } else {
+                content.extend_from_slice(buf);
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+// where a scan looks next: entries follow each other at a fixed distance, the header size
+// plus whatever padding aligns them, which the first two entries found in a row give away.
+// A binary may hold several tables, each padded its own way
+#[derive(Debug, Clone, Copy)]
+struct Stride {
+    header_size: usize,
+    pointer_size: usize,
+    // where the scan started, offsets out of reach of a table are aligned to it
+    origin: usize,
+    last_hit: Option<usize>,
+    step: Option<usize>,
+}
+
+impl Stride {
+    fn new(header_size: usize, pointer_size: usize, origin: usize) -> Self {
+        Self {
+            header_size,
+            pointer_size,
+            origin,
+            last_hit: None,
+            step: None,
+        }
+    }
+
+    // offset to try after an entry at `offset`
+    fn hit(&mut self, offset: usize) -> usize {
+        match self.last_hit.map(|last| offset - last) {
+            Some(distance) if self.following(distance) => {
+                if distance >= self.header_size {
+                    self.step = Some(distance);
+                }
+            }
+            // the first entry of another table, whose padding is yet to be seen
+            _ => self.step = None,
+        }
+        self.last_hit = Some(offset);
+        offset + self.step.unwrap_or(self.header_size)
+    }
+
+    // offset to try after nothing was found at `offset`; up to a header past the last entry,
+    // where the padding may still be unknown, every byte, then aligned pointers again
+    fn miss(&self, offset: usize) -> usize {
+        match self.last_hit {
+            Some(last) if self.following(offset - last) => offset + 1,
+            _ => offset + self.pointer_size - (offset - self.origin) % self.pointer_size,
+        }
+    }
+
+    // whether a scan at `offset` is within reach of the entry found last
+    fn in_table(&self, offset: usize) -> bool {
+        self.last_hit
+            .is_some_and(|last| self.following(offset - last))
+    }
+
+    fn following(&self, distance: usize) -> bool {
+        distance < 2 * self.header_size
+    }
+}
+
+#[derive(Debug)]
+struct SectionInfo {
+    name: String,
+    virtual_address: u64,
+    file_offset: u64,
+    size: u64,
+}
+
+/// Scans an executable for the asset table Tauri embeds at build time.
+pub struct Dumper {
+    // the mapped file or an owned buffer
+    backing: Box<dyn AsRef<[u8]> + Send + Sync>,
+    // range of the selected architecture slice in `backing`, the whole file unless it is a fat Mach-O
+    slice: Range<usize>,
+    // !for Windows PE,
+    // - .rdata section
+    // !for Mach-O
+    // - __TEXT segment, __const section (address lookups only, unless nothing else matches)
+    // - __DATA segment, __const section
+    // - __DATA_CONST segment, __const section
+    // !for ELF
+    // - .rodata section
+    // - .data.rel.ro section
+    sections: Vec<SectionInfo>,
+    // vaddr -> file offset translation: ELF and Mach-O segments, every PE section
+    segments: Vec<SectionInfo>,
+    // pointers left to the loader, vaddr -> target: ELF R_*_RELATIVE addends (PIE), Mach-O
+    // chained fixups
+    relocations: HashMap<u64, u64>,
+    binary_format: BinaryFormat,
+    // 8 for 64-bit targets, 4 for 32-bit
+    pointer_size: usize,
+    little_endian: bool,
+    architecture: Architecture,
+    options: ScanOptions,
+    // set for memory images, where pointers translate to `pointer - image_base`
+    image_base: Option<u64>,
+    // field order found by a scan, unless the options force one
+    detected_layout: OnceLock<HeaderLayout>,
+}
+
+impl Dumper {
+    /// Opens and maps the binary at `path`.
+    ///
+    /// Binaries stored gzip or xz compressed are decompressed into memory first.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(File::open(path)?)
+    }
+
+    /// Maps `file` and locates the sections the asset table may live in.
+    ///
+    /// Universal Mach-O binaries default to the host architecture slice.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new(file: File) -> Result<Self> {
+        Self::with_arch(file, None)
+    }
+
+    /// Like [`Dumper::new`], but picks the `arch` slice out of a universal Mach-O binary.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_arch(file: File, arch: Option<Architecture>) -> Result<Self> {
+        let mmap = unsafe { Mmap::map(&file)? };
+        Self::from_backing(Box::new(mmap), None, arch)
+    }
+
+    /// Like [`Dumper::with_arch`], but parses the binary as `format` (PE, Mach-O or ELF)
+    /// instead of detecting it, for headers the detection gets wrong.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_format(
+        file: File,
+        format: BinaryFormat,
+        arch: Option<Architecture>,
+    ) -> Result<Self> {
+        let mmap = unsafe { Mmap::map(&file)? };
+        Self::from_backing(Box::new(mmap), Some(format), arch)
+    }
+
+    /// Works on a binary already in memory, e.g. one unpacked from an installer.
+    pub fn from_bytes(data: impl AsRef<[u8]> + Send + Sync + 'static) -> Result<Self> {
+        Self::from_bytes_with_arch(data, None)
+    }
+
+    /// Like [`Dumper::from_bytes`], but picks the `arch` slice out of a universal Mach-O binary.
+    pub fn from_bytes_with_arch(
+        data: impl AsRef<[u8]> + Send + Sync + 'static,
+        arch: Option<Architecture>,
+    ) -> Result<Self> {
+        Self::from_backing(Box::new(data), None, arch)
+    }
+
+    /// Like [`Dumper::from_bytes_with_arch`], but parses the binary as `format`, see
+    /// [`Dumper::with_format`].
+    pub fn from_bytes_with_format(
+        data: impl AsRef<[u8]> + Send + Sync + 'static,
+        format: BinaryFormat,
+        arch: Option<Architecture>,
+    ) -> Result<Self> {
+        Self::from_backing(Box::new(data), Some(format), arch)
+    }
+
+    fn from_backing(
+        backing: Box<dyn AsRef<[u8]> + Send + Sync>,
+        format: Option<BinaryFormat>,
+        arch: Option<Architecture>,
+    ) -> Result<Self> {
+        let bytes = (*backing).as_ref();
+        if let Some(unpacked) = unpack_input(bytes)? {
+            return Self::from_backing(Box::new(unpacked), format, arch);
+        }
+        let slice = match format {
+            None => Self::select_slice(bytes, arch)?,
+            // a forced format skips the detection, universal binaries aside
+            Some(BinaryFormat::MachO) => Self::select_slice(bytes, arch).unwrap_or(0..bytes.len()),
+            Some(_) => 0..bytes.len(),
+        };
+        let obj = parse_object(&bytes[slice.clone()], format)?;
+        if let Some(arch) = arch.filter(|&arch| arch != obj.architecture()) {
+            return Err(DumperError::UnsupportedFormat(format!(
+                "requested architecture {:?}, but binary is {:?}",
+                arch,
+                obj.architecture()
+            )));
+        }
+        if is_upx_packed(&obj, &bytes[slice.clone()]) {
+            return Err(DumperError::UnsupportedFormat(
+                "binary is packed with UPX, its assets are compressed on disk; \
+                 unpack it first (e.g. `upx -d`)"
+                    .to_string(),
+            ));
+        }
+        let binary_format = obj.format();
+        let architecture = obj.architecture();
+        let pointer_size = if obj.is_64() { 8 } else { 4 };
+        let little_endian = obj.is_little_endian();
+
+        // find .rdata or similar section
+        let sections = match binary_format {
+            BinaryFormat::Pe => obj
+                .sections()
+                .filter(is_pe_rdata)
+                .filter_map(|s| {
+                    Some(SectionInfo {
+                        name: s.name().unwrap_or_default().to_string(),
+                        virtual_address: s.address(),
+                        // sections without file backing (e.g. zero-fill) cannot hold the table
+                        file_offset: s.file_range()?.0,
+                        size: s.size(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            BinaryFormat::MachO => obj
+                .sections()
+                .filter(|s| {
+                    matches!(
+                        s.segment_name(),
+                        Ok(Some("__TEXT" | "__DATA_CONST" | "__DATA"))
+                    )
+                })
+                .filter(|s| s.name() == Ok("__const"))
+                .filter_map(|s| {
+                    Some(SectionInfo {
+                        name: format!(
+                            "{},{}",
+                            s.segment_name().ok().flatten().unwrap_or_default(),
+                            s.name().unwrap_or_default()
+                        ),
+                        virtual_address: s.address(),
+                        file_offset: s.file_range()?.0,
+                        size: s.size(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            BinaryFormat::Elf => [".rodata", ".data.rel.ro"]
+                .iter()
+                .filter_map(|name| obj.section_by_name(name))
+                .filter_map(|s| {
+                    Some(SectionInfo {
+                        name: s.name().unwrap_or_default().to_string(),
+                        virtual_address: s.address(),
+                        file_offset: s.file_range()?.0,
+                        size: s.size(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+            other_format => {
+                return Err(DumperError::UnsupportedFormat(format!(
+                    "unsupported binary format: {:?}",
+                    other_format
+                )))
+            }
+        };
+
+        let segments = match binary_format {
+            BinaryFormat::Elf | BinaryFormat::MachO => obj
+                .segments()
+                .map(|s| SectionInfo {
+                    name: s.name().ok().flatten().unwrap_or_default().to_string(),
+                    virtual_address: s.address(),
+                    file_offset: s.file_range().0,
+                    size: s.file_range().1,
+                })
+                .collect::<Vec<_>>(),
+            // names and data may live in any section, not just the scanned ones
+            BinaryFormat::Pe => obj
+                .sections()
+                .filter_map(|s| {
+                    Some(SectionInfo {
+                        name: s.name().unwrap_or_default().to_string(),
+                        virtual_address: s.address(),
+                        file_offset: s.file_range()?.0,
+                        size: s.file_range()?.1,
+                    })
+                })
+                .collect::<Vec<_>>(),
+            _ => Vec::new(),
+        };
+        let relocations = match binary_format {
+            BinaryFormat::Elf => Self::collect_relative_relocations(&obj),
+            BinaryFormat::MachO => {
+                fixups::chained_fixups(&bytes[slice.clone()]).unwrap_or_else(|e| {
+                    log::debug!("no chained fixups: {:#}", e);
+                    HashMap::new()
+                })
+            }
+            _ => HashMap::new(),
+        };
+
+        Ok(Self {
+            backing,
+            slice,
+            sections,
+            segments,
+            relocations,
+            binary_format,
+            pointer_size,
+            little_endian,
+            architecture,
+            options: ScanOptions::default(),
+            image_base: None,
+            detected_layout: OnceLock::new(),
+        })
+    }
+
+    /// Maps the memory image at `path`, see [`Dumper::from_memory_image`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open_memory_image(
+        path: impl AsRef<Path>,
+        image_base: u64,
+        arch: Option<Architecture>,
+    ) -> Result<Self> {
+        let mmap = unsafe { Mmap::map(&File::open(path)?)? };
+        Self::from_memory_image(mmap, image_base, arch)
+    }
+
+    /// Works on a flat memory image, e.g. dumped from a running process, mapped at
+    /// `image_base`: the asset table is already relocated and its pointers translate to
+    /// `pointer - image_base`.
+    ///
+    /// An image has no headers, so the pointer width and byte order come from `arch` (by
+    /// default the host architecture); PowerPC, SPARC and s390x are taken to be big endian.
+    pub fn from_memory_image(
+        data: impl AsRef<[u8]> + Send + Sync + 'static,
+        image_base: u64,
+        arch: Option<Architecture>,
+    ) -> Result<Self> {
+        let architecture = arch.unwrap_or_else(host_architecture);
+        let pointer_size = architecture
+            .address_size()
+            .map(|size| size.bytes() as usize)
+            .ok_or_else(|| {
+                DumperError::UnsupportedFormat(format!(
+                    "pointer size of {:?} is unknown, pick another architecture",
+                    architecture
+                ))
+            })?;
+        let little_endian = !matches!(
+            architecture,
+            Architecture::PowerPc
+                | Architecture::PowerPc64
+                | Architecture::Sparc64
+                | Architecture::S390x
+        );
+
+        let backing: Box<dyn AsRef<[u8]> + Send + Sync> = Box::new(data);
+        let len = (*backing).as_ref().len();
+        let image = || SectionInfo {
+            name: "image".to_string(),
+            virtual_address: image_base,
+            file_offset: 0,
+            size: len as u64,
+        };
+        Ok(Self {
+            backing,
+            slice: 0..len,
+            sections: vec![image()],
+            segments: vec![image()],
+            relocations: HashMap::new(),
+            binary_format: host_binary_format(),
+            pointer_size,
+            little_endian,
+            architecture,
+            options: ScanOptions::default(),
+            image_base: Some(image_base),
+            detected_layout: OnceLock::new(),
+        })
+    }
+
+    fn select_slice(data: &[u8], arch: Option<Architecture>) -> Result<Range<usize>> {
+        let arches = match FileKind::parse(data).map_err(|e| not_executable(data, e))? {
+            FileKind::MachOFat32 => MachOFatFile32::parse(data)?
+                .arches()
+                .iter()
+                .map(|a| (a.architecture(), a.file_range()))
+                .collect::<Vec<_>>(),
+            FileKind::MachOFat64 => MachOFatFile64::parse(data)?
+                .arches()
+                .iter()
+                .map(|a| (a.architecture(), a.file_range()))
+                .collect::<Vec<_>>(),
+            _ => return Ok(0..data.len()),
+        };
+
+        let (_, (offset, size)) = match arch {
+            Some(arch) => arches.iter().find(|(a, _)| *a == arch).ok_or_else(|| {
+                DumperError::UnsupportedFormat(format!(
+                    "architecture {:?} not found, available: {:?}",
+                    arch,
+                    arches.iter().map(|(a, _)| a).collect::<Vec<_>>()
+                ))
+            })?,
+            None => arches
+                .iter()
+                .find(|(a, _)| *a == host_architecture())
+                .or(arches.first())
+                .ok_or_else(|| {
+                    DumperError::UnsupportedFormat("empty universal binary".to_string())
+                })?,
+        };
+
+        let (offset, size) = (*offset as usize, *size as usize);
+        if offset.saturating_add(size) > data.len() {
+            return Err(DumperError::UnsupportedFormat(
+                "architecture slice is out of range".to_string(),
+            ));
+        }
+        Ok(offset..offset + size)
+    }
+
+    fn data(&self) -> &[u8] {
+        &(*self.backing).as_ref()[self.slice.clone()]
+    }
+
+    /// Replaces the options used by subsequent scans.
+    pub fn set_options(&mut self, options: ScanOptions) {
+        self.options = options;
+    }
+
+    pub fn options(&self) -> &ScanOptions {
+        &self.options
+    }
+
+    /// Architecture of the binary (or of the selected slice).
+    pub fn architecture(&self) -> Architecture {
+        self.architecture
+    }
+
+    /// Container format of the binary; for a memory image, the host's native format.
+    pub fn binary_format(&self) -> BinaryFormat {
+        self.binary_format
+    }
+
+    /// Field order of the asset table entries: forced by the options, detected by an earlier
+    /// scan, or the usual one.
+    pub fn header_layout(&self) -> HeaderLayout {
+        self.options
+            .layout
+            .or(self.detected_layout.get().copied())
+            .unwrap_or_default()
+    }
+
+    /// Number of separate asset tables the `entries` found by a scan belong to, e.g. one per
+    /// window or plugin: entries further apart than the padding a table may have start
+    /// another one.
+    pub fn count_tables<'a>(&self, entries: impl IntoIterator<Item = &'a TableEntry>) -> usize {
+        let mut offsets: Vec<_> = entries.into_iter().map(|entry| entry.offset).collect();
+        offsets.sort_unstable();
+        offsets.dedup();
+        let reach = 2 * self.asset_header_size() as u64;
+        let gaps = offsets.windows(2).filter(|w| w[1] - w[0] >= reach).count();
+        gaps + usize::from(!offsets.is_empty())
+    }
+
+    /// Width of a pointer (and of every asset table field) in bytes, from the object header.
+    pub fn pointer_size(&self) -> usize {
+        self.pointer_size
+    }
+
+    pub fn is_little_endian(&self) -> bool {
+        self.little_endian
+    }
+
+    /// Address the memory image is mapped at, `None` for an executable file.
+    pub fn image_base(&self) -> Option<u64> {
+        self.image_base
+    }
+
+    fn collect_relative_relocations(obj: &object::File) -> HashMap<u64, u64> {
+        let Some(relocations) = obj.dynamic_relocations() else {
+            return HashMap::new();
+        };
+
+        relocations
+            .filter(|(_, r)| {
+                let RelocationFlags::Elf { r_type } = r.flags() else {
+                    return false;
+                };
+                matches!(
+                    (obj.architecture(), r_type),
+                    (Architecture::X86_64, elf::R_X86_64_RELATIVE)
+                        | (Architecture::Aarch64, elf::R_AARCH64_RELATIVE)
+                        | (Architecture::Arm, elf::R_ARM_RELATIVE)
+                        | (Architecture::I386, elf::R_386_RELATIVE)
+                        | (Architecture::Riscv64, elf::R_RISCV_RELATIVE)
+                        | (Architecture::PowerPc, elf::R_PPC_RELATIVE)
+                        | (Architecture::PowerPc64, elf::R_PPC64_RELATIVE)
+                        | (Architecture::S390x, elf::R_390_RELATIVE)
+                        | (Architecture::Sparc64, elf::R_SPARC_RELATIVE)
+                )
+            })
+            // REL-style relocations keep the addend in place, nothing to patch
+            .filter(|(_, r)| !r.has_implicit_addend())
+            .map(|(offset, r)| (offset, r.addend() as u64))
+            .collect()
+    }
+
+    fn convert_file_offset_to_va(&self, offset: u64) -> Option<u64> {
+        self.sections
+            .iter()
+            .find(|s| offset >= s.file_offset && offset < s.file_offset + s.size)
+            .map(|s| offset - s.file_offset + s.virtual_address)
+    }
+
+    fn read_pointer(&self, offset: usize, value: u64) -> u64 {
+        if self.relocations.is_empty() {
+            return value;
+        }
+        self.convert_file_offset_to_va(offset as u64)
+            .and_then(|va| self.relocations.get(&va).copied())
+            .unwrap_or(value)
+    }
+
+    // the file offset the pointer `rva` maps to
+    fn resolve_rva(&self, rva: u64) -> Option<u64> {
+        if self.image_base.is_some() {
+            return Self::translate(&self.segments, rva);
+        }
+        match self.binary_format {
+            BinaryFormat::MachO => {
+                // pointers missing from the chained fixups: low 48 bit; classic rebases leave
+                // the vmaddr, chained fixups an offset
+                let value = rva & 0xFFFFFFFFFFFF;
+                Some(Self::translate(&self.segments, value).unwrap_or(value))
+            }
+            BinaryFormat::Pe | BinaryFormat::Elf => Self::translate(&self.segments, rva),
+            _ => None,
+        }
+    }
+
+    fn translate(regions: &[SectionInfo], rva: u64) -> Option<u64> {
+        regions
+            .iter()
+            .find(|s| rva >= s.virtual_address && rva < s.virtual_address + s.size)
+            .map(|s| rva - s.virtual_address + s.file_offset)
+    }
+
+    // looks at the name fields only: nearly every scan offset fails here, long before the
+    // header is decoded or any codec runs
+    fn plausible_entry(&self, offset: usize, layout: HeaderLayout) -> bool {
+        self.entry_name(offset, layout)
+            .is_some_and(|name| Self::starts_like_name(name, &self.options.name_prefix))
+    }
+
+    // raw name bytes of the table entry at `offset`, if its name fields point anywhere
+    fn entry_name(&self, offset: usize, layout: HeaderLayout) -> Option<&[u8]> {
+        let pointer_size = self.pointer_size;
+        let fields = self.data().get(offset..offset + self.asset_header_size())?;
+        let field = |index: usize| &fields[index * pointer_size..];
+        let name_len = self.read_word(field(layout.name_len()));
+        if name_len == 0 || name_len > MAX_NAME_LEN {
+            return None;
+        }
+        let name_ptr_offset = offset + layout.name_ptr() * pointer_size;
+        let name_ptr = self.read_pointer(name_ptr_offset, self.read_word(field(layout.name_ptr())));
+        let name = self.resolve_rva(name_ptr)? as usize;
+        self.data().get(name..name.checked_add(name_len as usize)?)
+    }
+
+    fn asset_header_size(&self) -> usize {
+        4 * self.pointer_size
+    }
+
+    // reads one pointer-sized field, `bytes` must hold at least `pointer_size` bytes
+    fn read_word(&self, bytes: &[u8]) -> u64 {
+        match (self.pointer_size, self.little_endian) {
+            (8, true) => u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            (8, false) => u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            (_, true) => u32::from_le_bytes(bytes[..4].try_into().unwrap()) as u64,
+            (_, false) => u32::from_be_bytes(bytes[..4].try_into().unwrap()) as u64,
+        }
+    }
+
+    fn read_asset_header(&self, chunk: &[u8], layout: HeaderLayout) -> AssetHeader {
+        let field = |index: usize| self.read_word(&chunk[index * self.pointer_size..]);
+        AssetHeader {
+            name_ptr: field(layout.name_ptr()),
+            name_len: field(layout.name_len()),
+            data_ptr: field(layout.data_ptr()),
+            data_size: field(layout.data_len()),
+        }
+    }
+
+    fn scan_ranges(&self) -> Result<Vec<Range<usize>>> {
+        let (start, len) = match (self.options.scan_start, self.options.scan_len) {
+            (None, None) => return self.section_ranges(),
+            (Some(start), len) => (start, len),
+            (None, len) => {
+                let first = self.section_ranges()?.into_iter().next();
+                (first.map_or(0, |r| r.start as u64), len)
+            }
+        };
+
+        let size = self.data().len() as u64;
+        if start > size {
+            return Err(DumperError::InvalidScanRange(format!(
+                "scan start {start:#x} is past the end of the file ({size:#x} bytes)"
+            )));
+        }
+        let end = match len {
+            Some(len) => start
+                .checked_add(len)
+                .ok_or_else(|| DumperError::InvalidScanRange("scan range overflows".to_string()))?,
+            None => size,
+        };
+        if end > size {
+            return Err(DumperError::InvalidScanRange(format!(
+                "scan range {start:#x}..{end:#x} is out of bounds (file is {size:#x} bytes)"
+            )));
+        }
+        Ok(std::iter::once(start as usize..end as usize).collect())
+    }
+
+    /// The Tauri release the binary was built with, guessed from the strings embedded in it.
+    pub fn tauri_version(&self) -> Option<TauriVersion> {
+        version::tauri_version(self.data())
+    }
+
+    /// Names of the sections the heuristic scan walks through.
+    pub fn scanned_sections(&self) -> Vec<&str> {
+        if let Some(name) = &self.options.section {
+            return vec![name.as_str()];
+        }
+        self.scan_sections()
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect()
+    }
+
+    fn scan_sections(&self) -> Vec<&SectionInfo> {
+        match self.binary_format {
+            BinaryFormat::MachO => {
+                // tables hold pointers, so they land in a data segment; __TEXT only has bytes
+                let data: Vec<_> = self
+                    .sections
+                    .iter()
+                    .filter(|s| !s.name.starts_with("__TEXT,"))
+                    .collect();
+                if data.is_empty() {
+                    self.sections.iter().collect()
+                } else {
+                    data
+                }
+            }
+            _ => self.sections.iter().collect(),
+        }
+    }
+
+    /// Every section of the binary, e.g. to find out why a scan came up empty.
+    pub fn sections(&self) -> Vec<Section> {
+        if self.image_base.is_some() {
+            return self
+                .sections
+                .iter()
+                .map(|s| Section {
+                    name: s.name.clone(),
+                    segment: None,
+                    kind: SectionKind::Data,
+                    address: s.virtual_address,
+                    size: s.size,
+                    file_range: Some((s.file_offset, s.size)),
+                })
+                .collect();
+        }
+        let Ok(obj) = parse_object(self.data(), Some(self.binary_format)) else {
+            return Vec::new();
+        };
+        obj.sections()
+            .map(|s| Section {
+                name: s.name().unwrap_or_default().to_string(),
+                segment: s.segment_name().ok().flatten().map(str::to_string),
+                kind: s.kind(),
+                address: s.address(),
+                size: s.size(),
+                file_range: s.file_range(),
+            })
+            .collect()
+    }
+
+    fn section_ranges(&self) -> Result<Vec<Range<usize>>> {
+        if let Some(name) = &self.options.section {
+            return self.named_section_range(name).map(|range| vec![range]);
+        }
+
+        let sections = match self.binary_format {
+            // the image is all there is
+            _ if self.image_base.is_some() => self.sections.iter().collect(),
+            BinaryFormat::Pe => {
+                let sections = self.scan_sections();
+                if sections.is_empty() {
+                    return Err(DumperError::UnsupportedFormat(format!(
+                        "no read-only data section found, available sections: {}",
+                        self.sections()
+                            .iter()
+                            .map(|s| s.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )));
+                }
+                sections
+            }
+            BinaryFormat::MachO => {
+                if self.sections.is_empty() {
+                    return Err(DumperError::UnsupportedFormat("empty sections".to_string()));
+                }
+                self.scan_sections()
+            }
+            BinaryFormat::Elf => {
+                // pointer tables land in .data.rel.ro for PIE, .rodata when statically linked
+                if self.sections.is_empty() {
+                    return Err(DumperError::UnsupportedFormat("empty sections".to_string()));
+                }
+                self.sections.iter().collect()
+            }
+            other_format => {
+                return Err(DumperError::UnsupportedFormat(format!(
+                    "unsupported binary format: {:?}",
+                    other_format
+                )))
+            }
+        };
+
+        let len = self.data().len() as u64;
+        Ok(sections
+            .into_iter()
+            .map(|s| {
+                let end = s.file_offset.saturating_add(s.size);
+                // e.g. an interrupted download: scan what made it into the file
+                if end > len {
+                    log::warn!(
+                        "section {} ({:#x}..{:#x}) runs past the end of the file ({:#x} bytes), \
+                         the binary looks truncated",
+                        s.name,
+                        s.file_offset,
+                        end,
+                        len
+                    );
+                }
+                s.file_offset.min(len) as usize..end.min(len) as usize
+            })
+            .collect())
+    }
+
+    fn named_section_range(&self, name: &str) -> Result<Range<usize>> {
+        let sections = self.sections();
+        let section = sections.iter().find(|s| s.is_named(name)).ok_or_else(|| {
+            DumperError::SectionNotFound {
+                name: name.to_string(),
+                available: sections.iter().map(Section::full_name).collect(),
+            }
+        })?;
+        let (offset, size) = section.file_range.ok_or_else(|| {
+            DumperError::InvalidScanRange(format!("section {} has no data in the file", name))
+        })?;
+        let end = offset.saturating_add(size);
+        if end > self.data().len() as u64 {
+            return Err(DumperError::InvalidScanRange(format!(
+                "section {} is out of range",
+                name
+            )));
+        }
+        Ok(offset as usize..end as usize)
+    }
+
+    /// Scans the candidate sections and returns every asset that passes validation.
+    ///
+    /// The sections are scanned in parallel chunks on the rayon thread pool; the assets come
+    /// back in table order. When the usual field order finds nothing and the options leave it
+    /// open, the other [`HeaderLayout`]s are tried and the one finding the most assets is kept
+    /// for later scans, see [`Dumper::header_layout`].
+    pub fn heuristic_search_assets(&self) -> Result<Vec<Asset>> {
+        self.par_search_assets(|_, _| {})
+    }
+
+    /// Like [`Dumper::heuristic_search_assets`], calling `on_progress(bytes_scanned,
+    /// total_bytes)` from the worker threads as chunks are done.
+    pub fn par_search_assets<F>(&self, on_progress: F) -> Result<Vec<Asset>>
+    where
+        F: Fn(u64, u64) + Sync,
+    {
+        if self.options.format == Some(AssetFormat::Asar) {
+            return Ok(self.asar_assets());
+        }
+        let ranges = match self.scan_ranges() {
+            Ok(ranges) => ranges,
+            Err(e) => {
+                let fallback = self.fallback_assets();
+                return if fallback.is_empty() {
+                    Err(e)
+                } else {
+                    Ok(fallback)
+                };
+            }
+        };
+        let mut assets = self.scan_layout(&ranges, self.header_layout(), &on_progress);
+        self.check_cancelled()?;
+        let undecided = self.options.layout.is_none() && self.detected_layout.get().is_none();
+        if undecided && assets.len() < FEW_ASSETS {
+            // another field order has to find more to beat the usual one
+            let mut detected = HeaderLayout::default();
+            for layout in &HeaderLayout::ALL[1..] {
+                let found = self.scan_layout(&ranges, *layout, &|_, _| {});
+                self.check_cancelled()?;
+                if found.len() > assets.len() {
+                    (detected, assets) = (*layout, found);
+                }
+            }
+            if detected != HeaderLayout::default() {
+                log::info!("detected asset header layout: {}", detected);
+            }
+            if !assets.is_empty() {
+                let _ = self.detected_layout.set(detected);
+            }
+        }
+        if assets.is_empty() {
+            return Ok(self.fallback_assets());
+        }
+        Ok(assets)
+    }
+
+    /// Like [`Dumper::par_search_assets`], for callbacks that cannot be shared between
+    /// threads, e.g. ones driving a progress bar of their own: the calls are serialized and
+    /// `bytes_scanned` only grows.
+    pub fn scan_with_progress<F>(&self, on_progress: F) -> Result<Vec<Asset>>
+    where
+        F: FnMut(u64, u64) + Send,
+    {
+        // chunks finish out of order, lower counts arriving late are dropped
+        let progress = Mutex::new((on_progress, 0));
+        self.par_search_assets(|scanned, total| {
+            let (on_progress, reported) = &mut *progress.lock().unwrap();
+            if scanned > *reported {
+                *reported = scanned;
+                on_progress(scanned, total);
+            }
+        })
+    }
+
+    // the longest run of entries with `ScanOptions::min_run` set and long enough, or else
+    // every entry in `ranges`
+    fn scan_layout<F>(
+        &self,
+        ranges: &[Range<usize>],
+        layout: HeaderLayout,
+        on_progress: &F,
+    ) -> Vec<Asset>
+    where
+        F: Fn(u64, u64) + Sync,
+    {
+        if let Some(min_run) = self.options.min_run {
+            match self.scan_run(ranges, layout, on_progress) {
+                Some(run) if run.count >= min_run.max(1) => {
+                    log::debug!(
+                        "{:#x}: table of {} entries, {:#x} bytes apart",
+                        run.start,
+                        run.count,
+                        run.step
+                    );
+                    let assets: Vec<_> = run
+                        .offsets()
+                        .collect::<Vec<_>>()
+                        .par_iter()
+                        .filter_map(|&offset| self.candidate_at(offset, None, layout))
+                        .collect();
+                    if !assets.is_empty() {
+                        return assets;
+                    }
+                }
+                run => log::info!(
+                    "longest run of entries is {}, short of {}, scanning every offset",
+                    run.map_or(0, |run| run.count),
+                    min_run
+                ),
+            }
+        }
+        self.scan_table(ranges, layout, on_progress)
+    }
+
+    // the longest run of consecutive entries in `ranges` that only point at plausible names
+    // and data, the padding between them may be anything up to another header
+    fn scan_run<F>(
+        &self,
+        ranges: &[Range<usize>],
+        layout: HeaderLayout,
+        on_progress: &F,
+    ) -> Option<Run>
+    where
+        F: Fn(u64, u64) + Sync,
+    {
+        let header_size = self.asset_header_size();
+        let total = ranges.iter().map(|r| r.len() as u64).sum();
+        let chunks: Vec<_> = ranges
+            .iter()
+            .flat_map(|range| {
+                range
+                    .clone()
+                    .step_by(SCAN_CHUNK)
+                    .map(move |start| (start..(start + SCAN_CHUNK).min(range.end), range.end))
+            })
+            .collect();
+        let scanned = AtomicU64::new(0);
+        let valid: HashSet<usize> = chunks
+            .par_iter()
+            .flat_map_iter(|(offsets, end)| {
+                let found: Vec<_> = offsets
+                    .clone()
+                    .step_by(self.pointer_size)
+                    .take_while(|offset| offset + header_size <= *end && !self.cancelled())
+                    .filter(|&offset| {
+                        self.plausible_entry(offset, layout)
+                            && self.resolve_entry(offset, layout).is_ok()
+                    })
+                    .collect();
+                let done = scanned.fetch_add(offsets.len() as u64, Ordering::Relaxed);
+                on_progress(done + offsets.len() as u64, total);
+                found
+            })
+            .collect();
+
+        let valid = &valid;
+        (header_size..2 * header_size)
+            .step_by(self.pointer_size)
+            .flat_map(|step| {
+                // only runs starting at an entry with none before it, the rest are their tails
+                valid
+                    .iter()
+                    .filter(move |&&offset| offset < step || !valid.contains(&(offset - step)))
+                    .map(move |&start| Run {
+                        start,
+                        step,
+                        count: (0..)
+                            .take_while(|i| valid.contains(&(start + i * step)))
+                            .count(),
+                    })
+            })
+            // ties go to the earliest, tightest run, whatever order the set iterates in
+            .max_by_key(|run| (run.count, Reverse(run.start), Reverse(run.step)))
+    }
+
+    // the table entries laid out as `layout` in `ranges`, deduplicated
+    fn scan_table<F>(
+        &self,
+        ranges: &[Range<usize>],
+        layout: HeaderLayout,
+        on_progress: &F,
+    ) -> Vec<Asset>
+    where
+        F: Fn(u64, u64) + Sync,
+    {
+        let total = ranges.iter().map(|r| r.len() as u64).sum();
+        // chunks only split the start offsets, a header may run into the next chunk
+        let chunks: Vec<_> = ranges
+            .iter()
+            .flat_map(|range| {
+                range
+                    .clone()
+                    .step_by(SCAN_CHUNK)
+                    .map(move |start| (start..(start + SCAN_CHUNK).min(range.end), range.end))
+            })
+            .collect();
+        let scanned = AtomicU64::new(0);
+        let found: Vec<Vec<Asset>> = chunks
+            .par_iter()
+            .map(|(offsets, end)| {
+                let assets = self.scan_chunk(offsets.clone(), *end, layout);
+                let done = scanned.fetch_add(offsets.len() as u64, Ordering::Relaxed);
+                on_progress(done + offsets.len() as u64, total);
+                assets
+            })
+            .collect();
+
+        // several candidate headers may alias the same entry, also across chunks
+        let mut seen = HashSet::new();
+        found
+            .into_iter()
+            .flatten()
+            .filter(|asset| seen.insert((asset.name.clone(), asset.data_offset)))
+            .collect()
+    }
+
+    // assets whose entries start in `offsets`, entries may extend up to `end`
+    fn scan_chunk(&self, offsets: Range<usize>, end: usize, layout: HeaderLayout) -> Vec<Asset> {
+        let header_size = self.asset_header_size();
+        let mut stride = Stride::new(header_size, self.pointer_size, offsets.start);
+        let mut assets = Vec::new();
+        let mut offset = offsets.start;
+        // a table running into the next chunk is followed to its end, the entries may not be
+        // aligned the way that chunk is scanned
+        while (offset < offsets.end || stride.in_table(offset))
+            && offset + header_size <= end
+            && !self.cancelled()
+        {
+            match self.candidate_at(offset, None, layout) {
+                Some(asset) => {
+                    assets.push(asset);
+                    offset = stride.hit(offset);
+                }
+                None => offset = stride.miss(offset),
+            }
+        }
+        assets
+    }
+
+    fn cancelled(&self) -> bool {
+        self.options
+            .cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    fn check_cancelled(&self) -> Result<()> {
+        if self.cancelled() {
+            return Err(DumperError::Cancelled);
+        }
+        Ok(())
+    }
+
+    // validates the entry at `offset`, logging why it is rejected
+    fn candidate_at(
+        &self,
+        offset: usize,
+        name: Option<&str>,
+        layout: HeaderLayout,
+    ) -> Option<Asset> {
+        let plausible = match name {
+            Some(name) => self.entry_name(offset, layout) == Some(name.as_bytes()),
+            None => self.plausible_entry(offset, layout),
+        };
+        if !plausible {
+            log::trace!("{:#x}: rejected, implausible name", offset);
+            return None;
+        }
+        // the name checks out, so this may well be an asset the scan drops
+        self.parse_asset(offset, layout)
+            .inspect_err(|e| {
+                let name = self.entry_name(offset, layout).unwrap_or_default();
+                log::debug!(
+                    "{:#x}: near miss {:?} rejected, {:#}",
+                    offset,
+                    String::from_utf8_lossy(name),
+                    e
+                )
+            })
+            .ok()
+    }
+
+    // PE resources, or else ASAR archives, stand in for an asset table only found missing by
+    // the default scan
+    fn fallback_assets(&self) -> Vec<Asset> {
+        // an explicit range asks for the table there and nowhere else
+        if self.options.section.is_some() || self.options.scan_start.is_some() {
+            return Vec::new();
+        }
+        let resources = self.resource_assets().unwrap_or_else(|e| {
+            log::debug!("no resources: {:#}", e);
+            Vec::new()
+        });
+        if !resources.is_empty() || self.options.format == Some(AssetFormat::Tauri) {
+            return resources;
+        }
+        self.asar_assets()
+    }
+
+    /// Calls `f` with every asset as soon as it is found, until it returns
+    /// [`ControlFlow::Break`] or the scan is over.
+    pub fn for_each_asset<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(Asset) -> ControlFlow<()>,
+    {
+        for asset in self.assets() {
+            if f(asset?).is_break() {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decompresses every asset into a map from its name to its content, e.g. to serve them
+    /// in-process. Assets sharing a name with the same content count once, `on_collision`
+    /// picks between the others.
+    pub fn extract_to_map(&self, on_collision: NameCollision) -> Result<HashMap<String, Vec<u8>>> {
+        let mut contents = HashMap::new();
+        for asset in self.assets() {
+            let mut asset = asset?;
+            let content = match asset.decompressed.take() {
+                Some(decompressed) => decompressed,
+                None => self.decompress_asset(&asset)?,
+            };
+            match contents.entry(asset.name) {
+                Entry::Vacant(entry) => {
+                    entry.insert(content);
+                }
+                Entry::Occupied(entry) if *entry.get() == content => {}
+                Entry::Occupied(mut entry) => match on_collision {
+                    NameCollision::KeepFirst => {}
+                    NameCollision::KeepLast => {
+                        entry.insert(content);
+                    }
+                    NameCollision::Error => {
+                        return Err(DumperError::NameCollision(entry.key().clone()))
+                    }
+                },
+            }
+        }
+        Ok(contents)
+    }
+
+    /// Lazily scans the candidate sections, yielding each asset as soon as it is found.
+    pub fn assets(&self) -> AssetIter<'_> {
+        let (ranges, error, fallback) = match self.options.format {
+            Some(AssetFormat::Asar) => (Vec::new(), None, Some(self.asar_assets().into_iter())),
+            _ => match self.scan_ranges() {
+                Ok(ranges) => (ranges, None, None),
+                Err(e) => (Vec::new(), Some(e), None),
+            },
+        };
+
+        AssetIter {
+            dumper: self,
+            total: ranges.iter().map(|r| r.len() as u64).sum(),
+            ranges: ranges.into_iter(),
+            current: 0..0,
+            range_start: 0,
+            stride: Stride::new(self.asset_header_size(), self.pointer_size, 0),
+            error,
+            scanned: 0,
+            reported: None,
+            on_progress: None,
+            seen: HashSet::new(),
+            fallback,
+            name: None,
+            layout: self.header_layout(),
+        }
+    }
+
+    /// Scans for the asset called `name`, stopping at the first match.
+    ///
+    /// Entries with other names are skipped before their data is decoded, so this is much
+    /// cheaper than filtering [`Dumper::assets`].
+    pub fn find_asset(&self, name: &str) -> Result<Option<Asset>> {
+        let mut assets = self.assets();
+        assets.name = Some(name);
+        assets
+            .find_map(|asset| match asset {
+                Ok(asset) if asset.name != name => None,
+                asset => Some(asset),
+            })
+            .transpose()
+    }
+
+    /// Parses and validates the asset table entry at `offset` in the binary (or slice).
+    pub fn asset_at(&self, offset: usize) -> Result<Asset> {
+        self.parse_asset(offset, self.header_layout())
+            .map_err(|e| DumperError::InvalidAsset {
+                offset: offset as u64,
+                reason: format!("{:#}", e),
+            })
+    }
+
+    fn parse_asset(&self, offset: usize, layout: HeaderLayout) -> anyhow::Result<Asset> {
+        let entry = self.resolve_entry(offset, layout)?;
+        let (codec, decompressed) = self.decode_candidate(&self.data()[entry.data.clone()])?;
+
+        let name = self.retrieve_asset_name(entry.name.start, entry.name.len())?;
+        let data = self.retrieve_asset_data(entry.data.start, entry.data.len())?;
+        log::debug!(
+            "{:#x}: {} name {:#x} -> {:#x}, data {:#x} -> {:#x}, {} bytes {}",
+            offset,
+            name,
+            entry.table_entry.name_address,
+            entry.name.start,
+            entry.table_entry.data_address,
+            entry.data.start,
+            data.len(),
+            codec
+        );
+
+        Ok(Asset {
+            name,
+            data,
+            data_offset: entry.data.start as u64,
+            table_entry: Some(entry.table_entry),
+            codec,
+            decompressed,
+        })
+    }
+
+    // the entry at `offset`, checked to point at a plausible name and at data within the
+    // binary, short of decoding the data
+    fn resolve_entry(
+        &self,
+        offset: usize,
+        layout: HeaderLayout,
+    ) -> Result<ResolvedEntry, RejectReason> {
+        let header_size = self.asset_header_size();
+        if offset + header_size > self.data().len() {
+            return Err(RejectReason::EntryOutOfRange(offset));
+        }
+
+        let chunk = &self.data()[offset..offset + header_size];
+
+        let header = self.read_asset_header(chunk, layout);
+
+        let field_offset = |index: usize| offset + index * self.pointer_size;
+        let name_ptr = self.read_pointer(field_offset(layout.name_ptr()), header.name_ptr);
+        let data_ptr = self.read_pointer(field_offset(layout.data_ptr()), header.data_ptr);
+
+        let name_off = self
+            .resolve_rva(name_ptr)
+            .ok_or(RejectReason::UnmappedName(name_ptr))?;
+        let data_off = self
+            .resolve_rva(data_ptr)
+            .ok_or(RejectReason::UnmappedData(data_ptr))?;
+
+        self.validate_asset_pointers(name_off, header.name_len, data_off, header.data_size)?;
+        let (name_off, data_off) = (name_off as usize, data_off as usize);
+        Ok(ResolvedEntry {
+            table_entry: TableEntry {
+                offset: offset as u64,
+                name_address: name_ptr,
+                data_address: data_ptr,
+            },
+            name: name_off..name_off + header.name_len as usize,
+            data: data_off..data_off + header.data_size as usize,
+        })
+    }
+
+    fn validate_asset_pointers(
+        &self,
+        name_ptr: u64,
+        name_len: u64,
+        data_ptr: u64,
+        data_size: u64,
+    ) -> Result<(), RejectReason> {
+        let name_offset = name_ptr as usize;
+        let data_offset = data_ptr as usize;
+
+        if name_len == 0 || name_len > MAX_NAME_LEN {
+            return Err(RejectReason::NameLength(name_len));
+        }
+        if data_size > self.options.max_asset_size {
+            return Err(RejectReason::DataTooLarge(data_size));
+        }
+
+        // check if pointers are in the file range
+        if name_offset >= self.data().len()
+            || name_offset.saturating_add(name_len as usize) > self.data().len()
+        {
+            return Err(RejectReason::NameOutOfRange {
+                ptr: name_ptr,
+                len: name_len,
+            });
+        }
+        if data_offset >= self.data().len()
+            || data_offset.saturating_add(data_size as usize) > self.data().len()
+        {
+            return Err(RejectReason::DataOutOfRange {
+                ptr: data_ptr,
+                len: data_size,
+            });
+        }
+
+        // an asset is stored apart from its name, entries whose ranges share bytes are noise
+        if name_offset < data_offset.saturating_add(data_size as usize)
+            && data_offset < name_offset + name_len as usize
+        {
+            return Err(RejectReason::Overlap {
+                name: name_offset,
+                data: data_offset,
+            });
+        }
+
+        // check name format before paying for the decode
+        Self::check_asset_name(
+            &self.data()[name_offset..name_offset + name_len as usize],
+            &self.options.name_prefix,
+        )?;
+        Ok(())
+    }
+
+    // picks the codec of candidate asset data, decoding it unless validation is off
+    fn decode_candidate(&self, data: &[u8]) -> Result<(Codec, Option<Vec<u8>>), RejectReason> {
+        if !self.options.validate_data {
+            let codec = match self.options.codec {
+                Some(codec) => codec,
+                None => {
+                    Codec::sniff_in(&self.options.codecs, data).ok_or(RejectReason::UnknownCodec)?
+                }
+            };
+            return Ok((codec, None));
+        }
+
+        // check the data decodes with the requested or one of the known codecs
+        match self.options.codec {
+            Some(codec) => Ok((
+                codec,
+                self.try_codec(codec, data)
+                    .ok_or(RejectReason::Undecodable(codec))?,
+            )),
+            None => self
+                .options
+                .codecs
+                .iter()
+                .find_map(|&codec| Some((codec, self.try_codec(codec, data)?)))
+                .ok_or(RejectReason::UnknownCodec),
+        }
+    }
+
+    // whether `data` decodes with `codec`, along with the content worth keeping: none for raw
+    // assets, which are the data itself, or past `ScanOptions::max_kept_decoded`
+    fn try_codec(&self, codec: Codec, data: &[u8]) -> Option<Option<Vec<u8>>> {
+        let mut kept = Capped {
+            limit: self.options.max_kept_decoded,
+            content: Some(Vec::new()),
+        };
+        if !codec.probe_into(data, &mut kept, self.brotli_options()) {
+            return None;
+        }
+        Some(kept.content.filter(|_| !matches!(codec, Codec::Identity)))
+    }
+
+    // names start with `prefix`, or without one like paths do: with a letter, a digit or one of
+    // `/._-@`
+    fn starts_like_name(name: &[u8], prefix: &str) -> bool {
+        match prefix {
+            "" => name
+                .first()
+                .is_some_and(|&c| c.is_ascii_alphanumeric() || b"/._-@".contains(&c) || c >= 0x80),
+            _ => name.starts_with(prefix.as_bytes()),
+        }
+    }
+
+    // asset names are URL paths: UTF-8, starting with `prefix`, printable (non-ASCII is fine)
+    fn check_asset_name<'n>(name: &'n [u8], prefix: &str) -> Result<&'n str, RejectReason> {
+        if !Self::starts_like_name(name, prefix) {
+            return Err(match prefix {
+                "" => RejectReason::NameNotPath,
+                _ => RejectReason::NamePrefix(prefix.to_string()),
+            });
+        }
+        let name = std::str::from_utf8(name).map_err(|_| RejectReason::NameNotUtf8)?;
+        if let Some(c) = name
+            .chars()
+            .find(|&c| c.is_control() || (c.is_whitespace() && c != ' '))
+        {
+            return Err(RejectReason::NameChar(c));
+        }
+        Ok(name)
+    }
+
+    /// Assets stored as PE resources (`.rsrc`) rather than in an asset table, as some
+    /// packagers do. [`Dumper::assets`] falls back to these when the table scan finds nothing.
+    pub fn resource_assets(&self) -> Result<Vec<Asset>> {
+        if self.binary_format != BinaryFormat::Pe || self.image_base.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let mut assets = Vec::new();
+        let resources =
+            resource::pe_resources(self.data(), self.pointer_size == 8).map_err(|e| {
+                DumperError::UnsupportedFormat(format!("malformed resource directory: {:#}", e))
+            })?;
+        for resource in resources {
+            let asset = self.parse_resource(&resource);
+            match asset {
+                Ok(asset) => {
+                    log::debug!(
+                        "resource {}: {:#x}, {} bytes {}",
+                        asset.name,
+                        asset.data_offset,
+                        asset.data.len(),
+                        asset.codec
+                    );
+                    assets.push(asset);
+                }
+                Err(e) => log::debug!("resource {}: rejected, {:#}", resource.name, e),
+            }
+        }
+        Ok(assets)
+    }
+
+    /// Files packed into Electron-style ASAR archives embedded in the binary, stored as-is.
+    /// [`Dumper::assets`] falls back to these when neither the table scan nor the PE
+    /// resources find anything.
+    pub fn asar_assets(&self) -> Vec<Asset> {
+        asar::asar_files(self.data())
+            .into_iter()
+            .filter_map(|file| {
+                if file.size as u64 > self.options.max_asset_size {
+                    log::debug!(
+                        "{}: rejected, data size {:#x} exceeds the maximum",
+                        file.name,
+                        file.size
+                    );
+                    return None;
+                }
+                let name = Self::check_asset_name(file.name.as_bytes(), "/")
+                    .inspect_err(|e| log::debug!("{}: rejected, {:#}", file.name, e))
+                    .ok()?;
+                // the archive stores files verbatim, a `.gz` inside is content, not an encoding
+                Some(Asset {
+                    name: name.to_string(),
+                    data: self.data()[file.offset..file.offset + file.size].to_vec(),
+                    data_offset: file.offset as u64,
+                    table_entry: None,
+                    codec: Codec::Identity,
+                    decompressed: None,
+                })
+            })
+            .collect()
+    }
+
+    fn parse_resource(&self, resource: &Resource) -> anyhow::Result<Asset> {
+        if resource.size as u64 > self.options.max_asset_size {
+            return Err(anyhow!(
+                "data size {:#x} exceeds the maximum",
+                resource.size
+            ));
+        }
+        let data = self
+            .data()
+            .get(resource.offset..resource.offset + resource.size)
+            .context("data is out of range")?;
+        let name = Self::check_asset_name(resource.name.as_bytes(), "/")?.to_string();
+        let (codec, decompressed) = self.decode_candidate(data)?;
+        Ok(Asset {
+            name,
+            data: data.to_vec(),
+            data_offset: resource.offset as u64,
+            table_entry: None,
+            codec,
+            decompressed,
+        })
+    }
+
+    fn retrieve_asset_name(&self, offset: usize, len: usize) -> anyhow::Result<String> {
+        Ok(Self::check_asset_name(
+            &self.data()[offset..offset + len],
+            &self.options.name_prefix,
+        )?
+        .to_string())
+    }
+
+    fn retrieve_asset_data(&self, offset: usize, len: usize) -> anyhow::Result<Vec<u8>> {
+        Ok(self.data()[offset..offset + len].to_vec())
+    }
+
+    /// Decompresses the embedded bytes of `asset` with the codec detected during the scan,
+    /// reusing the content decoded while validating it when there is one.
+    pub fn decompress_asset(&self, asset: &Asset) -> Result<Vec<u8>> {
+        match &asset.decompressed {
+            Some(decompressed) => Ok(decompressed.clone()),
+            None => asset.codec.decode_in(&asset.data, self.brotli_options()),
+        }
+    }
+
+    /// Like [`Dumper::decompress_asset`], writing the content to `out` as it is decoded: no
+    /// copy of it is made, which keeps the memory use flat for huge assets. On error `out`
+    /// may already hold part of the content.
+    pub fn decompress_asset_to(&self, asset: &Asset, mut out: impl Write) -> Result<()> {
+        match &asset.decompressed {
+            Some(decompressed) => Ok(out.write_all(decompressed)?),
+            None => asset
+                .codec
+                .decode_into(&asset.data, &mut out, self.brotli_options()),
+        }
+    }
+
+    fn brotli_options(&self) -> BrotliOptions<'_> {
+        BrotliOptions {
+            buffer_size: self.options.brotli_buffer_size,
+            dictionary: self
+                .options
+                .brotli_dictionary
+                .as_deref()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Iterator returned by [`Dumper::assets`].
+pub struct AssetIter<'a> {
+    dumper: &'a Dumper,
+    ranges: std::vec::IntoIter<Range<usize>>,
+    current: Range<usize>,
+    stride: Stride,
+    error: Option<DumperError>,
+    range_start: usize,
+    // bytes of the finished ranges, and of all ranges
+    scanned: u64,
+    total: u64,
+    reported: Option<u64>,
+    on_progress: Option<Box<dyn FnMut(u64, u64) + 'a>>,
+    // (name, data offset) of the assets yielded so far
+    seen: HashSet<(String, u64)>,
+    // PE resources or ASAR files, once the table scan came up empty
+    fallback: Option<std::vec::IntoIter<Asset>>,
+    // only entries with this name are validated, see `Dumper::find_asset`
+    name: Option<&'a str>,
+    layout: HeaderLayout,
+}
+
+impl<'a> AssetIter<'a> {
+    /// Calls `f(bytes_scanned, total_bytes)` periodically while scanning.
+    pub fn on_progress(mut self, f: impl FnMut(u64, u64) + 'a) -> Self {
+        self.on_progress = Some(Box::new(f));
+        self
+    }
+
+    // switches to the PE resources or ASAR files, true if there are any
+    fn fall_back(&mut self) -> bool {
+        if self.fallback.is_some() {
+            return false;
+        }
+        let assets = self.dumper.fallback_assets();
+        let found = !assets.is_empty();
+        self.fallback = Some(assets.into_iter());
+        found
+    }
+
+    fn report_progress(&mut self, scanned: u64, force: bool) {
+        if let Some(f) = &mut self.on_progress {
+            let due = self
+                .reported
+                .is_none_or(|reported| scanned - reported >= PROGRESS_INTERVAL);
+            if force || due {
+                self.reported = Some(scanned);
+                f(scanned, self.total);
+            }
+        }
+    }
+}
+
+impl Iterator for AssetIter<'_> {
+    type Item = Result<Asset>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(fallback) = &mut self.fallback {
+            return fallback.next().map(Ok);
+        }
+        if let Some(e) = self.error.take() {
+            // e.g. no `.rdata` at all, yet the assets may be resources
+            if self.fall_back() {
+                return self.next();
+            }
+            return Some(Err(e));
+        }
+
+        let header_size = self.dumper.asset_header_size();
+        loop {
+            if self.dumper.cancelled() {
+                // an empty fallback ends the iteration after the error
+                self.fallback = Some(Vec::new().into_iter());
+                return Some(Err(DumperError::Cancelled));
+            }
+            if self.current.start + header_size > self.current.end {
+                self.scanned += (self.current.end - self.range_start) as u64;
+                let Some(range) = self.ranges.next() else {
+                    self.report_progress(self.total, true);
+                    if self.seen.is_empty() && self.fall_back() {
+                        return self.next();
+                    }
+                    return None;
+                };
+                self.range_start = range.start;
+                self.current = range;
+                self.stride = Stride::new(header_size, self.dumper.pointer_size, self.range_start);
+                continue;
+            }
+
+            let offset = self.current.start;
+            let asset = self.dumper.candidate_at(offset, self.name, self.layout);
+            self.current.start = match asset {
+                Some(_) => self.stride.hit(offset),
+                None => self.stride.miss(offset),
+            };
+
+            let scanned = self.scanned + (offset - self.range_start) as u64;
+            self.report_progress(scanned, false);
+
+            if let Some(asset) = asset {
+                // several candidate headers may alias the same entry
+                if self.seen.insert((asset.name.clone(), asset.data_offset)) {
+                    return Some(Ok(asset));
+                }
+            }
+        }
+    }
+}
+
+// decompresses binaries stored gzip or xz compressed, `None` for anything else
+fn unpack_input(data: &[u8]) -> Result<Option<Vec<u8>>> {
+    const XZ_MAGIC: [u8; 6] = [0xFD, b'7', b'z', b'X', b'Z', 0x00];
+
+    let unpacked = if data.starts_with(&XZ_MAGIC) {
+        let mut unpacked = Vec::new();
+        lzma_rs::xz_decompress(&mut &data[..], &mut unpacked).map_err(|e| {
+            DumperError::UnsupportedFormat(format!("input looks xz compressed, but {e}"))
+        })?;
+        unpacked
+    } else if Codec::sniff(data) == Some(Codec::Gzip) {
+        Codec::Gzip.decode(data).map_err(|e| {
+            DumperError::UnsupportedFormat(format!(
+                "input looks gzip compressed, but fails to decompress: {e}"
+            ))
+        })?
+    } else {
+        return Ok(None);
+    };
+    log::info!(
+        "decompressed the input, {} -> {} bytes",
+        data.len(),
+        unpacked.len()
+    );
+    Ok(Some(unpacked))
+}
+
+// parses `data` as `format`, or as whatever format it looks like
+fn parse_object(data: &[u8], format: Option<BinaryFormat>) -> Result<object::File<'_>> {
+    use object::File as Obj;
+
+    let obj = match format {
+        None => Obj::parse(data),
+        // the 32-bit layout is only worth trying once the 64-bit one is turned down
+        Some(BinaryFormat::Pe) => PeFile64::parse(data)
+            .map(Obj::Pe64)
+            .or_else(|e| PeFile32::parse(data).map(Obj::Pe32).map_err(|_| e)),
+        Some(BinaryFormat::MachO) => MachOFile64::parse(data)
+            .map(Obj::MachO64)
+            .or_else(|e| MachOFile32::parse(data).map(Obj::MachO32).map_err(|_| e)),
+        Some(BinaryFormat::Elf) => ElfFile64::parse(data)
+            .map(Obj::Elf64)
+            .or_else(|e| ElfFile32::parse(data).map(Obj::Elf32).map_err(|_| e)),
+        Some(other) => {
+            return Err(DumperError::UnsupportedFormat(format!(
+                "cannot parse binaries as {:?}, only as PE, Mach-O or ELF",
+                other
+            )))
+        }
+    };
+    obj.map_err(|e| not_executable(data, e))
+}
+
+// the parser errors alone ("Unknown file magic") do not tell users they picked the wrong file
+fn not_executable(data: &[u8], error: object::Error) -> DumperError {
+    const MAGIC_LEN: usize = 8;
+
+    let magic = match &data[..data.len().min(MAGIC_LEN)] {
+        [] => "the file is empty".to_string(),
+        head => format!(
+            "it starts with {} ({:?})",
+            head.iter()
+                .map(|b| format!("{:02x}", b))
+                .collect::<Vec<_>>()
+                .join(" "),
+            String::from_utf8_lossy(head)
+        ),
+    };
+    DumperError::UnsupportedFormat(format!(
+        "could not parse as an executable ({}); is this a Tauri binary? {}",
+        error, magic
+    ))
+}
+
+// UPX renames the PE sections and leaves its `UPX!` header near the start of every format
+fn is_upx_packed(obj: &object::File, data: &[u8]) -> bool {
+    let upx_section = obj
+        .sections()
+        .any(|s| matches!(s.name(), Ok("UPX0" | "UPX1" | ".UPX0" | ".UPX1")));
+    let head = &data[..data.len().min(0x1000)];
+    upx_section || head.windows(4).any(|w| w == b"UPX!")
+}
+
+// `.rdata` and friends, some linkers rename it or do not mark it as read-only data
+fn is_pe_rdata(section: &object::Section) -> bool {
+    let name = section.name().unwrap_or_default();
+    if !(name.starts_with(".rdata") || name == ".rodata") {
+        return false;
+    }
+    match section.flags() {
+        SectionFlags::Coff { characteristics } => {
+            characteristics & pe::IMAGE_SCN_MEM_READ != 0
+                && characteristics & pe::IMAGE_SCN_MEM_WRITE == 0
+        }
+        _ => section.kind() == SectionKind::ReadOnlyData,
+    }
+}
+
+fn host_binary_format() -> BinaryFormat {
+    match std::env::consts::OS {
+        "windows" => BinaryFormat::Pe,
+        "macos" | "ios" => BinaryFormat::MachO,
+        _ => BinaryFormat::Elf,
+    }
+}
+
+fn host_architecture() -> Architecture {
+    match std::env::consts::ARCH {
+        "x86_64" => Architecture::X86_64,
+        "x86" => Architecture::I386,
+        "aarch64" => Architecture::Aarch64,
+        "arm" => Architecture::Arm,
+        _ => Architecture::Unknown,
+    }
+}
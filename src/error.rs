@@ -0,0 +1,69 @@
+use std::io;
+use std::path::PathBuf;
+
+use crate::Codec;
+
+/// Result of the fallible library functions.
+pub type Result<T, E = DumperError> = std::result::Result<T, E>;
+
+/// Why a binary could not be opened or scanned, or an asset decoded.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DumperError {
+    /// Not an executable the dumper can scan, e.g. an unknown format, a missing architecture
+    /// slice or a packed binary.
+    #[error("{0}")]
+    UnsupportedFormat(String),
+    /// The section named by [`ScanOptions::section`](crate::ScanOptions::section) is missing.
+    #[error("section {name} not found, available sections: {}", available.join(", "))]
+    SectionNotFound {
+        name: String,
+        available: Vec<String>,
+    },
+    /// The scan options describe no range of the file.
+    #[error("{0}")]
+    InvalidScanRange(String),
+    /// No valid asset table entry at `offset`.
+    #[error("invalid asset at {offset:#x}: {reason}")]
+    InvalidAsset { offset: u64, reason: String },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    /// A manifest or report is not valid JSON.
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// The data is not a valid `codec` stream.
+    #[error("{codec}: {reason}")]
+    Decompression { codec: Codec, reason: String },
+    /// Several assets share a name but decode to different content, see
+    /// [`NameCollision::Error`](crate::NameCollision::Error).
+    #[error("several assets are named {0}, with different content")]
+    NameCollision(String),
+    /// An asset name with a `..` component or a drive or UNC prefix, which would be written
+    /// outside the output directory.
+    #[error("path traversal found: {0:?}")]
+    PathTraversal(String),
+    /// An asset name with a NUL byte, which no file system accepts.
+    #[error("NUL byte in asset name: {0:?}")]
+    Nul(String),
+    /// A directory resolving outside the output directory, e.g. through a symlink.
+    #[error("path escapes the output directory: {0:?}")]
+    OutsideOutput(PathBuf),
+    /// The scan was aborted through [`ScanOptions::cancel`](crate::ScanOptions::cancel).
+    #[error("scan cancelled")]
+    Cancelled,
+}
+
+impl DumperError {
+    pub(crate) fn decompression(codec: Codec, reason: impl ToString) -> Self {
+        Self::Decompression {
+            codec,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl From<object::Error> for DumperError {
+    fn from(error: object::Error) -> Self {
+        Self::UnsupportedFormat(error.to_string())
+    }
+}
@@ -0,0 +1,215 @@
+use anyhow::{anyhow, Context, Result};
+use object::macho;
+use object::read::macho::{LoadCommandVariant, MachOFile64};
+use object::{Endianness, Object, ObjectSegment};
+use std::collections::HashMap;
+
+// pointer formats of `dyld_chained_starts_in_segment`, see <mach-o/fixup-chains.h>
+const DYLD_CHAINED_PTR_ARM64E: u16 = 1;
+const DYLD_CHAINED_PTR_64: u16 = 2;
+const DYLD_CHAINED_PTR_32: u16 = 3;
+const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+const DYLD_CHAINED_PTR_ARM64E_USERLAND: u16 = 9;
+const DYLD_CHAINED_PTR_ARM64E_USERLAND24: u16 = 12;
+
+const DYLD_CHAINED_PTR_START_NONE: u16 = 0xFFFF;
+// 32-bit formats only: the page holds several chains, listed elsewhere
+const DYLD_CHAINED_PTR_START_MULTI: u16 = 0x8000;
+
+/// Walks the chained fixups (`LC_DYLD_CHAINED_FIXUPS`) of a 64-bit Mach-O binary, mapping the
+/// address of every rebased pointer to the address it points at.
+///
+/// The pointers are stored as chain links rather than addresses: besides the target, they
+/// hold the distance to the next link and, on arm64e, pointer authentication bits. Binds, to
+/// symbols of other images, are left out.
+pub(crate) fn chained_fixups(data: &[u8]) -> Result<HashMap<u64, u64>> {
+    let file = MachOFile64::<Endianness>::parse(data)?;
+    let endian = file.endian();
+    let mut blob = None;
+    let mut commands = file.macho_load_commands()?;
+    while let Some(command) = commands.next()? {
+        if let LoadCommandVariant::LinkeditData(linkedit) = command.variant()? {
+            if linkedit.cmd.get(endian) == macho::LC_DYLD_CHAINED_FIXUPS {
+                let offset = linkedit.dataoff.get(endian) as usize;
+                let size = linkedit.datasize.get(endian) as usize;
+                blob = Some(
+                    offset
+                        .checked_add(size)
+                        .and_then(|end| data.get(offset..end))
+                        .context("chained fixups are out of range")?,
+                );
+            }
+        }
+    }
+    let Some(blob) = blob else {
+        return Ok(HashMap::new());
+    };
+
+    // chains count from the start of the image, where __TEXT is mapped
+    let image_base = file
+        .segments()
+        .find(|s| s.name() == Ok(Some("__TEXT")))
+        .map_or(0, |s| s.address());
+    let segments: Vec<_> = file
+        .segments()
+        .map(|s| (s.address(), s.file_range()))
+        .collect();
+    let file_offset = |address: u64| {
+        segments
+            .iter()
+            .find(|(start, (_, size))| address >= *start && address - start < *size)
+            .and_then(|(start, (offset, _))| (address - start).checked_add(*offset))
+            .and_then(|offset| usize::try_from(offset).ok())
+    };
+
+    let reader = Reader(blob);
+    let starts = reader.u32(4)? as usize;
+    if starts >= blob.len() {
+        return Err(anyhow!("chained fixups starts are out of range"));
+    }
+    let mut fixups = HashMap::new();
+    for segment in 0..reader.u32(starts)? as usize {
+        let info = reader.u32(starts + 4 + 4 * segment)? as usize;
+        if info == 0 {
+            continue;
+        }
+        let info = starts
+            .checked_add(info)
+            .filter(|&info| info < blob.len())
+            .context("chained fixups segment is out of range")?;
+        let page_size = reader.u16(info + 4)? as u64;
+        let format = reader.u16(info + 6)?;
+        let segment_offset = reader.u64(info + 8)?;
+        let max_valid_pointer = reader.u32(info + 16)? as u64;
+        let Some(stride) = stride(format) else {
+            log::debug!("chained fixups: unsupported pointer format {}", format);
+            continue;
+        };
+
+        for page in 0..reader.u16(info + 20)? as u64 {
+            let start = reader.u16(info + 22 + 2 * page as usize)?;
+            if start == DYLD_CHAINED_PTR_START_NONE || start & DYLD_CHAINED_PTR_START_MULTI != 0 {
+                continue;
+            }
+            let Some(mut address) = page
+                .checked_mul(page_size)
+                .and_then(|page| image_base.checked_add(segment_offset)?.checked_add(page))
+                .and_then(|page| page.checked_add(start as u64))
+            else {
+                continue;
+            };
+            // links only go forward within the page, more means the chain is corrupt
+            for _ in 0..page_size / stride {
+                let Some(offset) = file_offset(address) else {
+                    break;
+                };
+                let link = match format {
+                    DYLD_CHAINED_PTR_32 => data
+                        .get(offset..offset.saturating_add(4))
+                        .map(|b| u32::from_le_bytes(b.try_into().unwrap()) as u64),
+                    _ => data
+                        .get(offset..offset.saturating_add(8))
+                        .map(|b| u64::from_le_bytes(b.try_into().unwrap())),
+                };
+                let Some(link) = link else {
+                    break;
+                };
+                let (target, next) = decode(format, link, max_valid_pointer);
+                let target = match target {
+                    Some(Target::Address(target)) => Some(target),
+                    Some(Target::Offset(target)) => image_base.checked_add(target),
+                    None => None,
+                };
+                if let Some(target) = target {
+                    fixups.insert(address, target);
+                }
+                match address.checked_add(next * stride) {
+                    Some(next_address) if next != 0 => address = next_address,
+                    _ => break,
+                }
+            }
+        }
+    }
+    log::debug!("chained fixups: {} rebased pointers", fixups.len());
+    Ok(fixups)
+}
+
+// bytes between the links of a chain, per `next` unit
+fn stride(format: u16) -> Option<u64> {
+    match format {
+        DYLD_CHAINED_PTR_ARM64E
+        | DYLD_CHAINED_PTR_ARM64E_USERLAND
+        | DYLD_CHAINED_PTR_ARM64E_USERLAND24 => Some(8),
+        DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET | DYLD_CHAINED_PTR_32 => Some(4),
+        _ => None,
+    }
+}
+
+// what a rebase points at, depending on the format
+enum Target {
+    Address(u64),
+    // from the start of the image
+    Offset(u64),
+}
+
+// the target of a chain link and the distance to the next one; binds have no target here
+fn decode(format: u16, link: u64, max_valid_pointer: u64) -> (Option<Target>, u64) {
+    match format {
+        DYLD_CHAINED_PTR_32 => {
+            let target = link & 0x3FF_FFFF;
+            // targets past the maximum are plain integers that happen to sit in the chain
+            let rebase = link >> 31 == 0 && target <= max_valid_pointer;
+            (
+                rebase.then_some(Target::Address(target)),
+                (link >> 26) & 0x1F,
+            )
+        }
+        DYLD_CHAINED_PTR_64 | DYLD_CHAINED_PTR_64_OFFSET => {
+            let target = link & 0xF_FFFF_FFFF;
+            let target = match format {
+                DYLD_CHAINED_PTR_64 => Target::Address(target),
+                _ => Target::Offset(target),
+            };
+            ((link >> 63 == 0).then_some(target), (link >> 51) & 0xFFF)
+        }
+        // arm64e: the top bits tell plain rebases, authenticated ones (an offset of 32 bits,
+        // next to the signing details) and binds apart; the high byte of plain rebases is a
+        // tag, not part of the address
+        _ => {
+            let next = (link >> 51) & 0x7FF;
+            let target = match (link >> 63, (link >> 62) & 1) {
+                (_, 1) => None,
+                (1, _) => Some(Target::Offset(link & 0xFFFF_FFFF)),
+                _ if format == DYLD_CHAINED_PTR_ARM64E => {
+                    Some(Target::Address(link & 0x7FF_FFFF_FFFF))
+                }
+                _ => Some(Target::Offset(link & 0x7FF_FFFF_FFFF)),
+            };
+            (target, next)
+        }
+    }
+}
+
+// little-endian fields of the fixups blob
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&self, at: usize) -> Result<[u8; N]> {
+        at.checked_add(N)
+            .and_then(|end| self.0.get(at..end))
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| anyhow!("chained fixups end at {:#x}", at))
+    }
+
+    fn u16(&self, at: usize) -> Result<u16> {
+        self.bytes(at).map(u16::from_le_bytes)
+    }
+
+    fn u32(&self, at: usize) -> Result<u32> {
+        self.bytes(at).map(u32::from_le_bytes)
+    }
+
+    fn u64(&self, at: usize) -> Result<u64> {
+        self.bytes(at).map(u64::from_le_bytes)
+    }
+}
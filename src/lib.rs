@@ -0,0 +1,34 @@
+//! Dump the frontend assets embedded in a Tauri application binary.
+
+mod asar;
+mod asset;
+mod codec;
+mod dumper;
+mod error;
+mod fixups;
+mod manifest;
+pub mod mime;
+mod options;
+pub mod path;
+mod resource;
+mod section;
+mod version;
+
+pub use asset::{Asset, TableEntry};
+pub use codec::{Codec, Decoder, DEFAULT_BROTLI_BUFFER_SIZE};
+pub use dumper::{AssetIter, Dumper};
+pub use error::{DumperError, Result};
+pub use manifest::{sha256_hex, Difference, DigestWriter, Manifest, ManifestEntry, Summary};
+pub use object::{Architecture, BinaryFormat, SectionKind};
+pub use options::{AssetFormat, HeaderLayout, NameCollision, ScanOptions, DEFAULT_MAX_ASSET_SIZE};
+pub use section::Section;
+pub use version::TauriVersion;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+/// Scans the binary at `path` and returns all embedded assets, still compressed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn extract_assets(path: &Path) -> Result<Vec<Asset>> {
+    Dumper::open(path)?.heuristic_search_assets()
+}
@@ -1,262 +1,2532 @@
 use anyhow::{anyhow, Context, Result};
-use clap::Parser;
-use memmap2::Mmap;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use clap::parser::ValueSource;
+use clap::{ArgAction, CommandFactory, Parser, ValueEnum};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use indicatif::{ProgressBar, ProgressStyle};
 use normalize_path::NormalizePath;
-use object::{BinaryFormat, Object, ObjectSection, SectionKind};
+use owo_colors::{OwoColorize, Stream, Style};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
 use std::fs::{self, File};
-use std::io::{Cursor, Read};
-use std::path::Path;
+use std::io::{self, BufWriter, Read, Write};
+use std::num::{NonZeroU64, NonZeroUsize};
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri_dumper::{
+    mime, path, sha256_hex, Architecture, Asset, AssetFormat, BinaryFormat, Codec, Difference,
+    DigestWriter, Dumper, DumperError, HeaderLayout, Manifest, ManifestEntry, ScanOptions, Summary,
+    TableEntry, DEFAULT_BROTLI_BUFFER_SIZE, DEFAULT_MAX_ASSET_SIZE,
+};
 
-const ASSET_HEADER_SIZE: usize = size_of::<AssetHeader>();
+// text for people goes to stdout, unless that carries the `--events` stream
+macro_rules! say {
+    ($($arg:tt)*) => {
+        if events_enabled() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+const EXIT_CODES: &str = "Exit codes:
+  0  success
+  1  any other error
+  2  the input is not a binary that can be dumped (unknown or unsupported format, packed)
+  3  no assets found
+  4  I/O error, e.g. the input cannot be read or the output cannot be written
+  5  the assets differ from the manifest given to --verify
+  6  the scan ran past --timeout";
+
+// errors with their own exit code, see `EXIT_CODES`
+#[derive(Debug, Clone, Copy)]
+enum Failure {
+    Unsupported = 2,
+    NoAssets = 3,
+    VerifyFailed = 5,
+    TimedOut = 6,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Unsupported => "Cannot dump this binary",
+            Self::NoAssets => "No assets found",
+            Self::VerifyFailed => "Verification failed",
+            Self::TimedOut => "Scan timed out",
+        })
+    }
+}
+
+impl std::error::Error for Failure {}
+
+fn exit_code(error: &anyhow::Error) -> u8 {
+    // also finds `Failure`s attached as context
+    if let Some(&failure) = error.downcast_ref::<Failure>() {
+        return failure as u8;
+    }
+    if error.chain().any(is_io_error) {
+        return 4;
+    }
+    1
+}
+
+// a yellow `Warning:`, plain when stderr is not a terminal or NO_COLOR is set
+fn warning() -> impl fmt::Display {
+    "Warning:".if_supports_color(Stream::Stderr, |text| {
+        text.style(Style::new().yellow().bold())
+    })
+}
+
+fn is_io_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    error.is::<io::Error>() || matches!(error.downcast_ref(), Some(DumperError::Io(_)))
+}
+
+// set once the arguments ask for `--events`
+static EVENTS: AtomicBool = AtomicBool::new(false);
 
-#[repr(C)]
-#[derive(Debug)]
-struct AssetHeader {
-    name_ptr: u64,
-    name_len: u64,
-    data_ptr: u64,
-    data_size: u64,
+fn events_enabled() -> bool {
+    EVENTS.load(Ordering::Relaxed)
 }
 
-#[derive(Debug)]
-struct Asset {
-    name: String,
-    data: Vec<u8>,
+// what `--events json` writes to stdout, one object per line tagged by `event`
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Event<'a> {
+    ScanStarted {
+        input: &'a str,
+        format: String,
+        architecture: String,
+        tauri_version: Option<String>,
+    },
+    AssetFound {
+        name: &'a str,
+        codec: String,
+        compressed_size: usize,
+        data_offset: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        table_entry: Option<TableEntry>,
+    },
+    AssetWritten {
+        name: &'a str,
+        // relative to the output directory, or the name in the archives
+        path: String,
+        decompressed_size: usize,
+        sha256: &'a str,
+        resumed: bool,
+    },
+    ScanComplete {
+        assets: usize,
+        // none for --count-only, which decompresses nothing
+        #[serde(skip_serializing_if = "Option::is_none")]
+        tables: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        compressed_bytes: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        decompressed_bytes: Option<usize>,
+    },
+    Error {
+        message: String,
+        // the asset skipped, none for the error the dump stops at
+        #[serde(skip_serializing_if = "Option::is_none")]
+        asset: Option<&'a str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        exit_code: Option<u8>,
+    },
 }
 
-#[derive(Debug)]
-struct SectionInfo {
-    virtual_address: u64,
-    file_offset: u64,
-    size: u64,
+fn emit(event: Event) {
+    if !events_enabled() {
+        return;
+    }
+    let line = serde_json::to_string(&event).expect("events serialize");
+    // a reader gone away is no reason to fail the dump
+    let _ = writeln!(io::stdout().lock(), "{}", line);
+}
+
+// warns that `asset` is left out because of `error`
+fn report_skipped(asset: &Asset, error: impl fmt::Display, bar: &ProgressBar) {
+    let message = format!("{:#}", error);
+    bar.suspend(|| eprintln!("{} skipping {}: {}", warning(), asset.name, message));
+    emit(Event::Error {
+        message,
+        asset: Some(&asset.name),
+        exit_code: None,
+    });
 }
 
 #[derive(Parser, Debug)]
-#[command(author, version, about)]
+#[command(author, version, about, after_help = EXIT_CODES)]
 struct Args {
+    /// Binary to dump, `-` reads it from stdin; gzip or xz compressed binaries are unpacked
     #[arg(short, long)]
     input: String,
 
+    #[arg(short, long, required_unless_present_any = ["list", "zip", "tar", "json_bundle", "verify", "baseline", "count_only", "stats_only"])]
+    output: Option<String>,
+
+    /// Write the assets into a zip archive, alongside or instead of --output
+    #[arg(long, value_name = "PATH")]
+    zip: Option<String>,
+
+    /// Write the assets into a reproducible .tar.gz, alongside or instead of --output
+    #[arg(long, value_name = "PATH")]
+    tar: Option<String>,
+
+    /// Write the assets into one JSON array of `{name, size, data_base64}` objects, e.g. for
+    /// a browser-based viewer
+    #[arg(long, value_name = "PATH")]
+    json_bundle: Option<String>,
+
+    /// Architecture slice to use for universal Mach-O binaries [default: host]
+    #[arg(long, value_name = "NAME", value_parser = parse_architecture)]
+    arch: Option<Architecture>,
+
+    /// Codec the assets are encoded with
+    #[arg(long, value_enum, default_value_t = CodecArg::Auto)]
+    codec: CodecArg,
+
+    /// How the assets are stored in the binary
+    #[arg(long, value_enum, default_value_t = FormatArg::Auto)]
+    format: FormatArg,
+
+    /// Parse the input as this kind of binary instead of detecting it from the headers
+    #[arg(long, value_enum, value_name = "FORMAT", conflicts_with = "image_base")]
+    binary_format: Option<BinaryFormatArg>,
+
+    /// Scan exactly this section, e.g. `.rdata` or `__DATA_CONST,__const`
+    #[arg(long, value_name = "NAME")]
+    section: Option<String>,
+
+    /// Treat the input as a flat memory image, e.g. dumped from a running process, mapped at
+    /// this address (in hex); --arch gives its pointer width [default: host]
+    #[arg(long, value_name = "HEX", value_parser = parse_hex)]
+    image_base: Option<u64>,
+
+    /// File offset to start scanning at, in hex
+    #[arg(long, value_name = "HEX", value_parser = parse_hex)]
+    scan_start: Option<u64>,
+
+    /// Number of bytes to scan, in hex
+    #[arg(long, value_name = "HEX", value_parser = parse_hex)]
+    scan_len: Option<u64>,
+
+    /// Take the asset table to be the longest run of at least N consecutive entries and
+    /// decode only that, ignoring matches elsewhere; without such a run, scan as usual
+    #[arg(long, value_name = "N")]
+    min_run: Option<NonZeroUsize>,
+
+    /// Reject candidates declaring more embedded bytes than this
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_MAX_ASSET_SIZE)]
+    max_asset_size: u64,
+
+    /// What the asset names in the table start with, e.g. `tauri://localhost/`; empty
+    /// accepts relative names
+    #[arg(long, value_name = "STR", default_value = "/")]
+    name_prefix: String,
+
+    /// Give up scanning after this many seconds
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<NonZeroU64>,
+
+    /// Size of the buffer brotli decodes into
+    #[arg(long, value_name = "BYTES", default_value_t = DEFAULT_BROTLI_BUFFER_SIZE)]
+    brotli_buffer: usize,
+
+    /// Custom dictionary the brotli streams were compressed against
+    #[arg(long, value_name = "PATH")]
+    brotli_dict: Option<PathBuf>,
+
+    /// List embedded assets without extracting them
     #[arg(short, long)]
-    output: String,
-}
-
-struct Dumper {
-    mmap: Mmap,
-    // !for Windows PE,
-    // - .rdata section
-    // !for Mach-O
-    // - __DATA segment, __const section
-    // - __DATA_CONST segment, __const section
-    sections: Vec<SectionInfo>,
-    binary_format: BinaryFormat,
-}
-
-impl Dumper {
-    fn new(file: File) -> Result<Self> {
-        let mmap = unsafe { Mmap::map(&file)? };
-        let obj = object::File::parse(&*mmap)?;
-        let binary_format = obj.format();
-
-        // find .rdata or similar section
-        let sections = match binary_format {
-            BinaryFormat::Pe => obj
-                .sections()
-                .filter(|s| s.name() == Ok(".rdata") && s.kind() == SectionKind::ReadOnlyData)
-                .map(|s| SectionInfo {
-                    virtual_address: s.address(),
-                    file_offset: s.file_range().unwrap().0,
-                    size: s.size(),
-                })
-                .collect::<Vec<_>>(),
-            BinaryFormat::MachO => obj
-                .sections()
-                .filter(|s| {
-                    s.segment_name() == Ok(Some("__TEXT"))
-                        || s.segment_name() == Ok(Some("__DATA_CONST"))
-                })
-                .filter(|s| s.name() == Ok("__const"))
-                .map(|s| SectionInfo {
-                    virtual_address: s.address(),
-                    file_offset: s.file_range().unwrap().0,
-                    size: s.size(),
-                })
-                .collect::<Vec<_>>(),
-            other_format => unimplemented!("Unsupport format: {:?}", other_format),
+    list: bool,
+
+    /// With --list, also show where each table entry, name and data sit in the binary
+    #[arg(long)]
+    offsets: bool,
+
+    /// With --list, hexdump the first N bytes of every decompressed asset
+    #[arg(long, value_name = "N", requires = "list")]
+    peek: Option<usize>,
+
+    /// Only count the assets, fast: candidates are checked without decoding their data, so
+    /// the count is approximate
+    #[arg(
+        long,
+        conflicts_with_all = ["list", "manifest", "verify", "baseline", "log_file", "report_json"]
+    )]
+    count_only: bool,
+
+    /// Decompress the assets in memory and print how many of each file extension there are,
+    /// and how large they are, without writing anything
+    #[arg(long, conflicts_with_all = ["list", "count_only", "output", "zip", "tar", "json_bundle"])]
+    stats_only: bool,
+
+    /// Write a JSON manifest of the assets (name, sizes, SHA-256) to this path
+    #[arg(long, value_name = "PATH")]
+    manifest: Option<String>,
+
+    /// Compare the decompressed assets against a manifest written by --manifest
+    #[arg(long, value_name = "MANIFEST")]
+    verify: Option<String>,
+
+    /// Report the assets added, removed and modified since the release a manifest written by
+    /// --manifest describes
+    // the assets --max-assets leaves unscanned would all count as removed
+    #[arg(
+        long,
+        visible_alias = "since",
+        value_name = "MANIFEST",
+        conflicts_with = "max_assets"
+    )]
+    baseline: Option<String>,
+
+    /// Only write the assets added or modified since --baseline
+    #[arg(long, requires = "baseline")]
+    changed_only: bool,
+
+    /// Write every asset straight into the output root under its file name, numbering clashes
+    #[arg(long)]
+    flat: bool,
+
+    /// Put every asset under this directory of the output (or archive), e.g. `appA` to keep
+    /// the dumps of several binaries in one tree apart; --clean then only empties it
+    #[arg(long, value_name = "PATH", value_parser = parse_prefix)]
+    prefix: Option<PathBuf>,
+
+    /// Name the output files after a template instead of the asset path, e.g.
+    /// `{index}-{hash}.{ext}`; placeholders: {path}, {dir}, {name}, {stem}, {ext}, {index}
+    /// (position in the --sort order) and {hash} (of the asset name)
+    #[arg(long, value_name = "TEMPLATE", value_parser = parse_template)]
+    name_template: Option<String>,
+
+    /// Replace characters and names Windows cannot store in file names (always done on
+    /// Windows)
+    #[arg(long)]
+    portable_names: bool,
+
+    /// What characters and names invalid on Windows are replaced with
+    #[arg(long, value_name = "STR", default_value = "_", value_parser = parse_replacement)]
+    replace_invalid: String,
+
+    /// Write the embedded (compressed) bytes as-is, with the codec's extension appended
+    #[arg(long)]
+    raw: bool,
+
+    /// Keep assets that decompress to nothing, usually false positives otherwise skipped
+    #[arg(long)]
+    keep_empty: bool,
+
+    /// Abort on the first asset that fails to decompress or write instead of skipping it
+    #[arg(long)]
+    strict: bool,
+
+    /// Lay the output out as a servable dist/ directory (drop query strings, add index.html)
+    #[arg(long)]
+    as_dist: bool,
+
+    /// Line endings of the text assets written out, binary ones are left untouched
+    #[arg(long, value_enum, default_value_t = Eol::Keep)]
+    eol: Eol,
+
+    /// Write assets with the same content once, the others become symlinks to it (a report
+    /// of the duplicates where symlinks are not supported)
+    #[arg(long)]
+    dedup: bool,
+
+    /// Empty the output directory before writing into it
+    #[arg(long)]
+    clean: bool,
+
+    /// Continue an interrupted dump: keep output files that already have the expected size
+    #[arg(long, conflicts_with = "clean")]
+    resume: bool,
+
+    /// Scan, decompress and resolve output paths without writing any files
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Stop scanning once this many assets are found
+    #[arg(long, value_name = "N")]
+    max_assets: Option<NonZeroUsize>,
+
+    /// Order in which assets are listed, dumped and recorded
+    #[arg(long, value_enum, default_value_t = SortKey::Name)]
+    sort: SortKey,
+
+    /// What to do when two different assets map to the same output path
+    #[arg(long, value_enum, default_value_t = ConflictPolicy::Overwrite)]
+    on_conflict: ConflictPolicy,
+
+    /// Print the sections of the binary before scanning
+    #[arg(long)]
+    debug_sections: bool,
+
+    /// Number of worker threads, 0 uses all cores
+    #[arg(short = 'j', long, value_name = "N", default_value_t = 0)]
+    threads: usize,
+
+    /// Print more details, e.g. per asset compression ratios; -vv logs every asset found,
+    /// -vvv every candidate offset and why it was rejected
+    #[arg(short, long, action = ArgAction::Count)]
+    verbose: u8,
+
+    /// Hide progress bars and the final summary
+    #[arg(short, long)]
+    quiet: bool,
+
+    /// Write what happens to stdout as events for programs driving the dump, the text meant
+    /// for people moves to stderr
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    events: Option<EventFormat>,
+
+    /// Also write the dumped assets and the summary to this file, headed by the input's SHA-256
+    #[arg(long, value_name = "PATH")]
+    log_file: Option<String>,
+
+    /// Write the extraction summary as JSON to this path
+    #[arg(long, value_name = "PATH")]
+    report_json: Option<String>,
+
+    /// Only handle assets matching this glob (repeatable), e.g. `**/*.js`
+    #[arg(long, value_name = "GLOB")]
+    include: Vec<String>,
+
+    /// Skip assets matching this glob (repeatable)
+    #[arg(long, value_name = "GLOB")]
+    exclude: Vec<String>,
+
+    /// Only handle the asset with exactly this name (repeatable), e.g. `/index.html`;
+    /// the scan stops as soon as all of them are found
+    #[arg(long, value_name = "NAME")]
+    only: Vec<String>,
+
+    /// Read defaults for these options from this TOML file, e.g. `include = ["**/*.js"]`;
+    /// flags on the command line win [default: tauri-dumper.toml, if present]
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CodecArg {
+    /// Try brotli, gzip, zstd, then raw bytes
+    Auto,
+    Brotli,
+    Gzip,
+    Zstd,
+    Raw,
+}
+
+impl CodecArg {
+    fn codec(self) -> Option<Codec> {
+        match self {
+            Self::Auto => None,
+            Self::Brotli => Some(Codec::Brotli),
+            Self::Gzip => Some(Codec::Gzip),
+            Self::Zstd => Some(Codec::Zstd),
+            Self::Raw => Some(Codec::Identity),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum FormatArg {
+    /// The Tauri asset table, falling back to PE resources and ASAR archives
+    Auto,
+    /// Only the Tauri asset table (and PE resources)
+    Tauri,
+    /// Only ASAR archives, as Electron packs them
+    Asar,
+}
+
+impl FormatArg {
+    fn format(self) -> Option<AssetFormat> {
+        match self {
+            Self::Auto => None,
+            Self::Tauri => Some(AssetFormat::Tauri),
+            Self::Asar => Some(AssetFormat::Asar),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum BinaryFormatArg {
+    /// Windows PE
+    Pe,
+    /// Mach-O, universal binaries included
+    Macho,
+    Elf,
+}
+
+impl BinaryFormatArg {
+    fn format(self) -> BinaryFormat {
+        match self {
+            Self::Pe => BinaryFormat::Pe,
+            Self::Macho => BinaryFormat::MachO,
+            Self::Elf => BinaryFormat::Elf,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum SortKey {
+    /// By asset name
+    Name,
+    /// By embedded size, largest first
+    Size,
+    /// By file offset of the data
+    Offset,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum EventFormat {
+    /// One JSON object per line: scan_started, asset_found, asset_written, scan_complete and
+    /// error events
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Eol {
+    /// As embedded
+    Keep,
+    /// Unix style, `\n`
+    Lf,
+    /// Windows style, `\r\n`
+    Crlf,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum ConflictPolicy {
+    /// Keep the first asset
+    Skip,
+    /// Keep the last asset
+    Overwrite,
+    /// Keep both, renaming the later one
+    Rename,
+}
+
+// record of a dump for `--log-file`, a no-op unless one was asked for
+#[derive(Default)]
+struct AuditLog(Option<BufWriter<File>>);
+
+impl AuditLog {
+    fn create(path: &str, input: &str, sha256: &str) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("failed to create log {path}"))?;
+        let mut log = Self(Some(BufWriter::new(file)));
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        log.line(&format!("tauri-dumper {}", env!("CARGO_PKG_VERSION")))?;
+        log.line(&format!("Input: {input}"))?;
+        log.line(&format!("SHA-256: {sha256}"))?;
+        log.line(&format!("Started: {started} (unix time)"))?;
+        log.line("")?;
+        Ok(log)
+    }
+
+    fn line(&mut self, line: &str) -> Result<()> {
+        if let Some(out) = &mut self.0 {
+            writeln!(out, "{line}")?;
+        }
+        Ok(())
+    }
+
+    fn summary(&mut self, summary: &Summary) -> Result<()> {
+        if let Some(out) = &mut self.0 {
+            writeln!(out)?;
+            write_summary(out, summary)?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        if let Some(mut out) = self.0 {
+            out.flush()?;
+        }
+        Ok(())
+    }
+}
+
+struct AssetFilter {
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    only: HashSet<String>,
+}
+
+impl AssetFilter {
+    fn new(include: &[String], exclude: &[String], only: &[String]) -> Result<Self> {
+        let include = if include.is_empty() {
+            None
+        } else {
+            Some(Self::build(include)?)
         };
 
         Ok(Self {
-            mmap,
-            sections,
-            binary_format,
+            include,
+            exclude: Self::build(exclude)?,
+            only: only.iter().cloned().collect(),
         })
     }
 
-    fn convert_rva_to_file_offset(&self, rva: u64) -> Result<u64> {
-        let rva = match self.binary_format {
-            BinaryFormat::MachO => Ok(rva & 0xFFFFFFFFFFFF), // low 48 bit
-            BinaryFormat::Pe => {
-                let section = self.sections.first().context("")?;
-                if rva >= section.virtual_address && rva < section.virtual_address + section.size {
-                    Ok(rva - section.virtual_address + section.file_offset)
-                } else {
-                    Err(anyhow!("invalid rva: {:#X}", rva))
-                }
+    fn build(patterns: &[String]) -> Result<GlobSet> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in patterns {
+            builder.add(Glob::new(pattern).with_context(|| format!("invalid glob: {pattern}"))?);
+        }
+        Ok(builder.build()?)
+    }
+
+    fn matches(&self, name: &str) -> bool {
+        if !self.only.is_empty() && !self.only.contains(name) {
+            return false;
+        }
+        let name = name.strip_prefix('/').unwrap_or(name);
+        self.include.as_ref().is_none_or(|set| set.is_match(name)) && !self.exclude.is_match(name)
+    }
+
+    // `--only` names not found yet, `None` when the whole binary has to be scanned
+    fn pending(&self) -> Option<HashSet<&str>> {
+        (!self.only.is_empty()).then(|| self.only.iter().map(String::as_str).collect())
+    }
+
+    // records a matched asset, true once every `--only` name has been seen
+    fn found(pending: &mut Option<HashSet<&str>>, name: &str) -> bool {
+        pending.as_mut().is_some_and(|pending| {
+            pending.remove(name);
+            pending.is_empty()
+        })
+    }
+
+    fn check_found(&self, manifest: &Manifest) -> Result<()> {
+        let mut missing: Vec<_> = self
+            .only
+            .iter()
+            .filter(|name| !manifest.assets.iter().any(|e| &e.name == *name))
+            .map(String::as_str)
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        missing.sort_unstable();
+        Err(anyhow!("Assets not found: {}", missing.join(", ")))
+    }
+}
+
+fn parse_architecture(name: &str) -> Result<Architecture, String> {
+    match name {
+        "x86_64" | "amd64" => Ok(Architecture::X86_64),
+        "x86" | "i386" => Ok(Architecture::I386),
+        "arm64" | "aarch64" => Ok(Architecture::Aarch64),
+        "arm" | "armv7" => Ok(Architecture::Arm),
+        "ppc" => Ok(Architecture::PowerPc),
+        "ppc64" => Ok(Architecture::PowerPc64),
+        "s390x" => Ok(Architecture::S390x),
+        _ => Err(format!("unknown architecture: {name}")),
+    }
+}
+
+// decoded content kept from the scan when the assets are streamed to their files
+const KEPT_DECODED: usize = 64 * 1024;
+
+const CONFIG_FILE: &str = "tauri-dumper.toml";
+
+const TEMPLATE_FIELDS: [&str; 7] = ["path", "dir", "name", "stem", "ext", "index", "hash"];
+
+fn parse_prefix(value: &str) -> Result<PathBuf, String> {
+    if value.starts_with(['/', '\\']) || Path::new(value).is_absolute() {
+        return Err("the prefix is a directory within the output, not an absolute path".into());
+    }
+    path::relative_path(value).map_err(|e| e.to_string())
+}
+
+fn parse_replacement(value: &str) -> Result<String, String> {
+    match value.chars().find(|&c| {
+        c.is_control() || matches!(c, '/' | '\\') || path::WINDOWS_INVALID_CHARS.contains(&c)
+    }) {
+        Some(c) => Err(format!("{:?} is not allowed in file names either", c)),
+        None => Ok(value.to_string()),
+    }
+}
+
+fn parse_template(value: &str) -> Result<String, String> {
+    expand_template(value, |_| String::new())?;
+    Ok(value.to_string())
+}
+
+// fills in the `{field}` placeholders of a --name-template
+fn expand_template(template: &str, field: impl Fn(&str) -> String) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let end = start + rest[start..].find('}').ok_or("unclosed '{' in template")?;
+        let name = &rest[start + 1..end];
+        if !TEMPLATE_FIELDS.contains(&name) {
+            return Err(format!(
+                "unknown placeholder {{{}}}, expected one of {}",
+                name,
+                TEMPLATE_FIELDS.map(|f| format!("{{{f}}}")).join(", ")
+            ));
+        }
+        expanded.push_str(&field(name));
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+fn parse_hex(value: &str) -> Result<u64, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u64::from_str_radix(digits, 16).map_err(|e| format!("invalid hex value {value}: {e}"))
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            // only --timeout cancels scans
+            let cancelled = error.chain().any(|e| {
+                matches!(
+                    e.downcast_ref::<DumperError>(),
+                    Some(DumperError::Cancelled)
+                )
+            });
+            let error = if cancelled {
+                error.context(Failure::TimedOut)
+            } else {
+                error
+            };
+            emit(Event::Error {
+                message: format!("{:#}", error),
+                asset: None,
+                exit_code: Some(exit_code(&error)),
+            });
+            let label = "Error:"
+                .if_supports_color(Stream::Stderr, |text| text.style(Style::new().red().bold()));
+            eprintln!("{} {:?}", label, error);
+            ExitCode::from(exit_code(&error))
+        }
+    }
+}
+
+// a setting of the config file, keyed by the long flag it stands for
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum ConfigValue {
+    Flag(bool),
+    Number(u64),
+    Text(String),
+    List(Vec<String>),
+}
+
+// parses the command line on top of the defaults read from the config file
+fn parse_args() -> Result<Args> {
+    let cli = std::env::args_os().collect::<Vec<_>>();
+    let command = Args::command();
+    // leniently, the config may be what provides --input
+    let given = match command
+        .clone()
+        .ignore_errors(true)
+        .try_get_matches_from(&cli)
+    {
+        Ok(given) => given,
+        Err(e) => e.exit(),
+    };
+
+    let (path, explicit) = match given.get_one::<PathBuf>("config") {
+        Some(path) => (path.clone(), true),
+        None => (PathBuf::from(CONFIG_FILE), false),
+    };
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => return Ok(Args::parse()),
+        Err(e) => return Err(anyhow::Error::new(e).context(format!("failed to read {:?}", path))),
+    };
+    let config: BTreeMap<String, ConfigValue> =
+        toml::from_str(&text).with_context(|| format!("{:?} is not a valid config", path))?;
+
+    let mut defaults = Vec::new();
+    for (key, value) in config {
+        let id = key.replace('-', "_");
+        let arg = command
+            .get_arguments()
+            .find(|arg| arg.get_id() == id.as_str() && arg.get_long().is_some() && id != "config")
+            .with_context(|| format!("{:?}: unknown option {:?}", path, key))?;
+        if given.value_source(&id) == Some(ValueSource::CommandLine) {
+            continue;
+        }
+        let flag = format!("--{}", arg.get_long().unwrap_or_default());
+        let takes_values = arg.get_action().takes_values();
+        match value {
+            ConfigValue::Flag(set) if !takes_values => defaults.extend(set.then(|| flag.clone())),
+            ConfigValue::Number(n) if matches!(arg.get_action(), ArgAction::Count) => {
+                defaults.extend((0..n).map(|_| flag.clone()))
+            }
+            ConfigValue::Number(n) if takes_values => defaults.push(format!("{}={}", flag, n)),
+            ConfigValue::Text(text) if takes_values => defaults.push(format!("{}={}", flag, text)),
+            ConfigValue::List(items) if takes_values => {
+                defaults.extend(items.iter().map(|item| format!("{}={}", flag, item)))
+            }
+            _ if takes_values => return Err(anyhow!("{:?}: {} takes a value", path, key)),
+            _ => return Err(anyhow!("{:?}: {} is either true or false", path, key)),
+        }
+    }
+    let (program, rest) = cli.split_first().context("no program name")?;
+    Ok(Args::parse_from(
+        std::iter::once(program.clone())
+            .chain(defaults.into_iter().map(Into::into))
+            .chain(rest.iter().cloned()),
+    ))
+}
+
+fn run() -> Result<()> {
+    let args = parse_args()?;
+    EVENTS.store(args.events.is_some(), Ordering::Relaxed);
+
+    env_logger::Builder::new()
+        .filter_level(match args.verbose {
+            0 => log::LevelFilter::Warn,
+            1 => log::LevelFilter::Info,
+            2 => log::LevelFilter::Debug,
+            _ => log::LevelFilter::Trace,
+        })
+        .format_timestamp(None)
+        .parse_default_env()
+        .init();
+
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(args.threads)
+        .build_global()?;
+
+    let mut log = AuditLog::default();
+    let mut dumper = if args.input == "-" {
+        let mut data = Vec::new();
+        std::io::stdin().read_to_end(&mut data)?;
+        if let Some(path) = &args.log_file {
+            log = AuditLog::create(path, "<stdin>", &sha256_hex(&data))?;
+        }
+        match (args.image_base, args.binary_format) {
+            (Some(base), _) => Dumper::from_memory_image(data, base, args.arch),
+            (None, Some(format)) => {
+                Dumper::from_bytes_with_format(data, format.format(), args.arch)
+            }
+            (None, None) => Dumper::from_bytes_with_arch(data, args.arch),
+        }
+        .map_err(unsupported)?
+    } else {
+        if let Some(path) = &args.log_file {
+            log = AuditLog::create(path, &args.input, &sha256_file(&args.input)?)?;
+        }
+        match (args.image_base, args.binary_format) {
+            (Some(base), _) => Dumper::open_memory_image(&args.input, base, args.arch),
+            (None, Some(format)) => {
+                Dumper::with_format(File::open(&args.input)?, format.format(), args.arch)
             }
-            other_format => unimplemented!("Unsupport format: {:?}", other_format),
+            (None, None) => Dumper::with_arch(File::open(&args.input)?, args.arch),
+        }
+        .map_err(unsupported)?
+    };
+    dumper.set_options(ScanOptions {
+        codec: args.codec.codec(),
+        format: args.format.format(),
+        section: args.section.clone(),
+        scan_start: args.scan_start,
+        scan_len: args.scan_len,
+        max_asset_size: args.max_asset_size,
+        brotli_buffer_size: args.brotli_buffer,
+        brotli_dictionary: args
+            .brotli_dict
+            .as_ref()
+            .map(|path| {
+                fs::read(path).with_context(|| format!("failed to read {}", path.display()))
+            })
+            .transpose()?,
+        validate_data: !args.count_only,
+        // streamed assets are decoded again into their files, only small ones are worth keeping
+        max_kept_decoded: if streams(&args) {
+            KEPT_DECODED
+        } else {
+            usize::MAX
+        },
+        min_run: args.min_run.map(usize::from),
+        name_prefix: args.name_prefix.clone(),
+        cancel: args
+            .timeout
+            .map(|secs| cancel_after(Duration::from_secs(secs.get()))),
+        ..Default::default()
+    });
+    let filter = AssetFilter::new(&args.include, &args.exclude, &args.only)?;
+    let baseline = args.baseline.as_deref().map(read_manifest).transpose()?;
+    if args.debug_sections {
+        print_sections(&dumper);
+    }
+    if args.verbose > 0 {
+        let format = match dumper.image_base() {
+            Some(base) => format!("memory image at {:#x},", base),
+            None => format!("{:?}", dumper.binary_format()),
         };
+        say!(
+            "Binary: {} {:?}, {}-bit {} endian",
+            format,
+            dumper.architecture(),
+            dumper.pointer_size() * 8,
+            if dumper.is_little_endian() {
+                "little"
+            } else {
+                "big"
+            }
+        );
+        say!(
+            "Scanning sections: {}",
+            dumper.scanned_sections().join(", ")
+        );
+    }
+
+    let version = dumper.tauri_version();
+    if !args.quiet {
+        match &version {
+            Some(version) => say!("Detected Tauri {}", version),
+            None => say!("Tauri version unknown, proceeding heuristically"),
+        }
+    }
+    emit(Event::ScanStarted {
+        input: &args.input,
+        format: match dumper.image_base() {
+            Some(_) => "memory image".to_string(),
+            None => format!("{:?}", dumper.binary_format()),
+        },
+        architecture: format!("{:?}", dumper.architecture()),
+        tauri_version: version.map(|version| version.to_string()),
+    });
+
+    if args.count_only {
+        let count = count_assets(&dumper, &args, &filter)?;
+        say!(
+            "Found ~{} assets (heuristic, asset data not verified)",
+            count
+        );
+        emit(Event::ScanComplete {
+            assets: count,
+            tables: None,
+            compressed_bytes: None,
+            decompressed_bytes: None,
+        });
+        return Ok(());
+    }
+
+    say!("Scanning for assets...");
+    let mut manifest = Manifest {
+        raw: args.raw,
+        ..Default::default()
+    };
+    // verifying alone only needs the hashes, nothing is extracted
+    let extract = args.output.is_some()
+        || args.zip.is_some()
+        || args.tar.is_some()
+        || args.json_bundle.is_some();
+    let count = if args.stats_only {
+        collect_assets(&dumper, &args, &filter, &mut manifest)
+    } else if args.list || !extract {
+        list_assets(&dumper, &args, &filter, &mut manifest)
+    } else {
+        dump_assets(
+            &dumper,
+            &args,
+            &filter,
+            baseline.as_ref(),
+            &mut manifest,
+            &mut log,
+        )
+    };
+    // the sections usually tell why the scan failed, e.g. the assets live somewhere unexpected
+    let count = count.inspect_err(|_| print_sections(&dumper))?;
+    say!(
+        "{}",
+        format!("Scanning completed. Found {} assets", count)
+            .if_supports_color(Stream::Stdout, |text| text.green())
+    );
+    log.line(&format!("Found {} assets", count))?;
+    let layout = dumper.header_layout();
+    if layout != HeaderLayout::default() {
+        say!("Detected asset header layout: {}", layout);
+        log.line(&format!("Header layout: {}", layout))?;
+    }
+    let tables = dumper.count_tables(
+        manifest
+            .assets
+            .iter()
+            .filter_map(|entry| entry.table_entry.as_ref()),
+    );
+    if tables > 1 {
+        say!("Found {} separate asset tables", tables);
+        log.line(&format!("Asset tables: {}", tables))?;
+    }
+    emit(Event::ScanComplete {
+        assets: count,
+        tables: Some(tables),
+        compressed_bytes: Some(manifest.assets.iter().map(|e| e.compressed_size).sum()),
+        decompressed_bytes: Some(manifest.assets.iter().map(|e| e.decompressed_size).sum()),
+    });
+    filter.check_found(&manifest)?;
+
+    if let Some(path) = &args.manifest {
+        manifest
+            .write(path)
+            .with_context(|| format!("failed to write manifest {:?}", path))?;
+        say!("Manifest written to {}", path);
+    }
+
+    if let Some(path) = &args.verify {
+        verify(&manifest, path)?;
+    }
+
+    if let (Some(path), Some(baseline)) = (&args.baseline, &baseline) {
+        report_changes(&manifest, baseline, &filter, path);
+    }
+
+    if count == 0 {
+        if !args.debug_sections {
+            print_sections(&dumper);
+        }
+        return Err(Failure::NoAssets.into());
+    }
+
+    if args.stats_only {
+        print_histogram(&manifest);
+    }
 
-        rva
+    let summary = Summary::new(&manifest, 5);
+    if let Some(path) = &args.report_json {
+        summary
+            .write(path)
+            .with_context(|| format!("failed to write report {:?}", path))?;
+        say!("Report written to {}", path);
     }
+    if !args.quiet {
+        print_summary(&summary);
+    }
+    log.summary(&summary)?;
+    log.finish()?;
+
+    say!(
+        "{}",
+        "Done :)".if_supports_color(Stream::Stdout, |text| text.green())
+    );
+
+    Ok(())
+}
+
+// a flag set once `timeout` is up, the timer outliving the scan is harmless
+fn cancel_after(timeout: Duration) -> Arc<AtomicBool> {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let flag = Arc::clone(&cancel);
+    thread::spawn(move || {
+        thread::sleep(timeout);
+        flag.store(true, Ordering::Relaxed);
+    });
+    cancel
+}
+
+// the binary was read fine, so anything but an I/O error means it cannot be dumped
+fn unsupported(error: DumperError) -> anyhow::Error {
+    match error {
+        DumperError::Io(_) => error.into(),
+        _ => anyhow::Error::new(error).context(Failure::Unsupported),
+    }
+}
+
+fn print_summary(summary: &Summary) {
+    let mut text = Vec::new();
+    let _ = write_summary(&mut text, summary);
+    let text = String::from_utf8_lossy(&text);
+    let text = text.if_supports_color(Stream::Stdout, |text| text.green());
+    // stdout going away mid-summary is not worth failing the dump for
+    if events_enabled() {
+        eprintln!("\n{}", text);
+    } else {
+        let _ = write!(io::stdout().lock(), "\n{}\n", text);
+    }
+}
+
+fn write_summary(out: &mut impl Write, summary: &Summary) -> io::Result<()> {
+    writeln!(out, "Summary")?;
+    writeln!(out, "  assets:        {}", summary.assets)?;
+    writeln!(out, "  compressed:    {} bytes", summary.compressed_bytes)?;
+    writeln!(out, "  decompressed:  {} bytes", summary.decompressed_bytes)?;
+    writeln!(out, "  ratio:         {:.2}", summary.compression_ratio)?;
+    writeln!(out, "  largest:")?;
+    for entry in &summary.largest {
+        writeln!(out, "    {:>12}  {}", entry.decompressed_size, entry.name)?;
+    }
+    Ok(())
+}
+
+fn sha256_file(path: &str) -> Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect())
+}
 
-    fn heuristic_search_assets(&self) -> Result<Vec<Asset>> {
-        // get start offset and scan length
-        let (scan_start, scan_length) = match self.binary_format {
-            BinaryFormat::Pe => {
-                let section = self.sections.first().context("empty sections")?;
-                (section.file_offset as usize, section.size as usize)
+fn read_manifest(path: &str) -> Result<Manifest> {
+    Manifest::read(path).with_context(|| format!("failed to read manifest {:?}", path))
+}
+
+fn verify(manifest: &Manifest, path: &str) -> Result<()> {
+    let expected = read_manifest(path)?;
+    let differences = manifest.verify(&expected);
+    for difference in &differences {
+        match difference {
+            Difference::Missing(name) => say!("Missing: {}", name),
+            Difference::Extra(name) => say!("Extra: {}", name),
+            Difference::Changed {
+                name,
+                expected,
+                actual,
+            } => say!("Changed: {} (expected {}, got {})", name, expected, actual),
+        }
+    }
+    if !differences.is_empty() {
+        return Err(
+            anyhow!("{} differences against {}", differences.len(), path)
+                .context(Failure::VerifyFailed),
+        );
+    }
+    say!("Verified {} assets against {}", manifest.assets.len(), path);
+
+    Ok(())
+}
+
+// the assets added, removed and modified since the `--baseline` manifest at `path`; assets
+// the filter leaves out were not looked at, so they are not removed
+fn report_changes(manifest: &Manifest, baseline: &Manifest, filter: &AssetFilter, path: &str) {
+    let baseline = Manifest {
+        raw: baseline.raw,
+        assets: baseline
+            .assets
+            .iter()
+            .filter(|entry| filter.matches(&entry.name))
+            .cloned()
+            .collect(),
+    };
+    let differences = manifest.verify(&baseline);
+    let (mut added, mut removed, mut modified) = (0, 0, 0);
+    for difference in &differences {
+        match difference {
+            Difference::Extra(name) => {
+                added += 1;
+                say!("Added: {}", name);
+            }
+            Difference::Missing(name) => {
+                removed += 1;
+                say!("Removed: {}", name);
             }
-            BinaryFormat::MachO => {
-                // search range always in __DATA_CONST,__const section
-                let section = self.sections.last().context("empty sections")?;
-                (section.file_offset as usize, section.size as usize)
+            Difference::Changed { name, .. } => {
+                modified += 1;
+                say!("Modified: {}", name);
             }
-            other_format => unimplemented!("Unsupport format: {:?}", other_format),
+        }
+    }
+    say!(
+        "Since {}: {} added, {} removed, {} modified, {} unchanged",
+        path,
+        added,
+        removed,
+        modified,
+        manifest.assets.len() - added - modified
+    );
+}
+
+fn print_sections(dumper: &Dumper) {
+    say!();
+    say!(
+        "{:<20} {:<16} {:<20} {:>18} {:>10}  file range",
+        "section",
+        "segment",
+        "kind",
+        "address",
+        "size"
+    );
+    for section in dumper.sections() {
+        let file_range = match section.file_range {
+            Some((offset, len)) => format!("{:#x}..{:#x}", offset, offset + len),
+            None => "-".to_string(),
         };
+        say!(
+            "{:<20} {:<16} {:<20} {:>#18x} {:>#10x}  {}",
+            section.name,
+            section.segment.as_deref().unwrap_or("-"),
+            format!("{:?}", section.kind),
+            section.address,
+            section.size,
+            file_range
+        );
+    }
+    say!();
+}
+
+fn progress_bar(args: &Args, len: u64, template: &str) -> ProgressBar {
+    // log lines would tear through the bar
+    if args.quiet || args.verbose > 1 {
+        return ProgressBar::hidden();
+    }
+
+    let bar = ProgressBar::new(len);
+    bar.set_style(
+        ProgressStyle::with_template(template)
+            .unwrap()
+            .progress_chars("=> "),
+    );
+    bar
+}
+
+fn scan_progress_bar(args: &Args) -> ProgressBar {
+    progress_bar(args, 0, "Scanning [{bar:40}] {bytes}/{total_bytes} ({eta})")
+}
+
+fn count_assets(dumper: &Dumper, args: &Args, filter: &AssetFilter) -> Result<usize> {
+    let bar = scan_progress_bar(args);
+    // only the whole scan looks for a --min-run table
+    if args.max_assets.is_none() || args.min_run.is_some() {
+        let assets = dumper.par_search_assets(|scanned, total| {
+            bar.set_length(total);
+            bar.set_position(scanned);
+        })?;
+        bar.finish_and_clear();
+        let matching = assets.iter().filter(|a| filter.matches(&a.name));
+        let empty = matching.clone().filter(|a| is_empty(a, args)).count();
+        report_empty(empty);
+        let count = (matching.count() - empty).min(args.max_assets.map_or(usize::MAX, usize::from));
+        report_limit(args, count);
+        return Ok(count);
+    }
+
+    let scan_bar = bar.clone();
+    let assets = dumper.assets().on_progress(move |scanned, total| {
+        scan_bar.set_length(total);
+        scan_bar.set_position(scanned);
+    });
+
+    let (mut count, mut empty) = (0, 0);
+    for asset in assets {
+        let asset = asset?;
+        if filter.matches(&asset.name) && is_empty(&asset, args) {
+            empty += 1;
+        } else if filter.matches(&asset.name) {
+            count += 1;
+            if limit_reached(args, count) {
+                break;
+            }
+        }
+    }
+    bar.finish_and_clear();
+    report_empty(empty);
+    report_limit(args, count);
+    Ok(count)
+}
+
+// whether the data of the asset is smaller than its content, as compressed data should be;
+// tiny streams are left alone, the codec framing outweighs what they save
+fn compressed(asset: &Asset, decompressed_size: usize) -> bool {
+    const MIN_CHECKED_SIZE: usize = 64;
+
+    matches!(asset.codec, Codec::Identity)
+        || asset.data.len() < MIN_CHECKED_SIZE
+        // a ratio of at least 1.01
+        || decompressed_size * 100 >= asset.data.len() * 101
+}
+
+// decoded to nothing, an empty brotli stream is as good as any for a bogus table entry
+fn is_empty(asset: &Asset, args: &Args) -> bool {
+    !args.keep_empty && asset.decompressed_len() == Some(0)
+}
+
+fn report_empty(count: usize) {
+    if count > 0 {
+        eprintln!(
+            "Note: skipped {} empty assets, pass --keep-empty to keep them",
+            count
+        );
+    }
+}
+
+fn limit_reached(args: &Args, count: usize) -> bool {
+    args.max_assets.map(usize::from) == Some(count)
+}
 
-        let end = scan_start.saturating_add(scan_length);
-        assert!(end <= self.mmap.len(), "scan end off is out of range");
+fn report_limit(args: &Args, count: usize) {
+    if limit_reached(args, count) {
+        eprintln!(
+            "Note: stopped after {} assets (--max-assets), the binary may hold more",
+            count
+        );
+    }
+}
 
-        let mut assets = Vec::new();
-        let mut offset = scan_start;
-        let mut scan_step = 8;
-        while offset + ASSET_HEADER_SIZE <= end {
-            if let Ok(asset) = self.parse_asset(offset) {
-                assets.push(asset);
-                scan_step = ASSET_HEADER_SIZE;
+// scans for the assets passing the filter, in `--sort` order
+fn scan_assets(dumper: &Dumper, args: &Args, filter: &AssetFilter) -> Result<Vec<Asset>> {
+    let bar = scan_progress_bar(args);
+    let mut pending = filter.pending();
+    let mut empty = 0;
+    // stopping early needs the assets one by one, in table order; only the whole scan looks
+    // for a --min-run table though, it is cut short afterwards
+    let mut assets = if (pending.is_none() && args.max_assets.is_none()) || args.min_run.is_some() {
+        let mut assets = dumper.par_search_assets(|scanned, total| {
+            bar.set_length(total);
+            bar.set_position(scanned);
+        })?;
+        assets.retain(|a| filter.matches(&a.name));
+        let found = assets.len();
+        assets.retain(|a| !is_empty(a, args));
+        empty = found - assets.len();
+        if let Some(max) = args.max_assets {
+            assets.truncate(max.get());
+        }
+        assets
+    } else {
+        scan_assets_until(dumper, args, filter, &bar, &mut pending, &mut empty)?
+    };
+    bar.finish_and_clear();
+    report_empty(empty);
+    report_limit(args, assets.len());
+    for asset in &assets {
+        emit(Event::AssetFound {
+            name: &asset.name,
+            codec: asset.codec.to_string(),
+            compressed_size: asset.data.len(),
+            data_offset: asset.data_offset,
+            table_entry: asset.table_entry,
+        });
+    }
+
+    // stable, so assets sharing a key keep their table order
+    match args.sort {
+        SortKey::Name => assets.sort_by(|a, b| a.name.cmp(&b.name)),
+        SortKey::Size => assets.sort_by_key(|a| std::cmp::Reverse(a.data.len())),
+        SortKey::Offset => assets.sort_by_key(|a| a.data_offset),
+    }
+    Ok(assets)
+}
+
+fn scan_assets_until(
+    dumper: &Dumper,
+    args: &Args,
+    filter: &AssetFilter,
+    bar: &ProgressBar,
+    pending: &mut Option<HashSet<&str>>,
+    empty: &mut usize,
+) -> Result<Vec<Asset>> {
+    let scan_bar = bar.clone();
+    let mut assets = Vec::new();
+    for asset in dumper.assets().on_progress(move |scanned, total| {
+        scan_bar.set_length(total);
+        scan_bar.set_position(scanned);
+    }) {
+        let asset = asset?;
+        if !filter.matches(&asset.name) {
+            continue;
+        }
+        let done = AssetFilter::found(pending, &asset.name);
+        if is_empty(&asset, args) {
+            *empty += 1;
+            if done {
+                break;
             }
+            continue;
+        }
+        assets.push(asset);
+        if done || limit_reached(args, assets.len()) {
+            break;
+        }
+    }
+    Ok(assets)
+}
 
-            offset += scan_step;
+// decompresses the assets into the manifest, printing nothing but warnings
+fn collect_assets(
+    dumper: &Dumper,
+    args: &Args,
+    filter: &AssetFilter,
+    manifest: &mut Manifest,
+) -> Result<usize> {
+    for asset in scan_assets(dumper, args, filter)? {
+        match dumper.decompress_asset(&asset) {
+            Ok(decompressed) => manifest.push(&asset, &decompressed),
+            Err(e) if args.strict => {
+                return Err(
+                    anyhow::Error::new(e).context(format!("failed to decompress {}", asset.name))
+                )
+            }
+            Err(e) => report_skipped(&asset, e, &ProgressBar::hidden()),
         }
+    }
+    Ok(manifest.assets.len())
+}
+
+// assets per file extension, the largest total first
+fn print_histogram(manifest: &Manifest) {
+    const BAR_WIDTH: usize = 30;
+
+    let mut types: HashMap<String, (usize, usize)> = HashMap::new();
+    for entry in &manifest.assets {
+        let extension = Path::new(&path::dist_name(&entry.name))
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()))
+            .unwrap_or_else(|| "(none)".to_string());
+        let (count, bytes) = types.entry(extension).or_default();
+        *count += 1;
+        *bytes += entry.decompressed_size;
+    }
+    let mut types: Vec<_> = types.into_iter().collect();
+    types.sort_by_key(|(extension, (count, bytes))| {
+        (
+            std::cmp::Reverse(*bytes),
+            std::cmp::Reverse(*count),
+            extension.clone(),
+        )
+    });
+
+    let largest = types.first().map_or(0, |(_, (_, bytes))| *bytes).max(1);
+    say!();
+    say!("{:<12} {:>7} {:>12}", "extension", "assets", "bytes");
+    for (extension, (count, bytes)) in &types {
+        // every type gets a mark, however small
+        let bar = "#".repeat((bytes * BAR_WIDTH).div_ceil(largest).max(1));
+        say!("{:<12} {:>7} {:>12}  {}", extension, count, bytes, bar);
+    }
+}
+
+fn list_assets(
+    dumper: &Dumper,
+    args: &Args,
+    filter: &AssetFilter,
+    manifest: &mut Manifest,
+) -> Result<usize> {
+    let mut count = 0;
+    let mut total_compressed = 0;
+    let mut total_decompressed = 0;
+
+    let assets = scan_assets(dumper, args, filter)?;
+    let location_header = if args.offsets {
+        format!(
+            "{:>10} {:>18} {:>18} {:>10}  ",
+            "entry", "name addr", "data addr", "offset"
+        )
+    } else {
+        String::new()
+    };
+    if args.verbose > 0 {
+        say!(
+            "{:>12} {:>12} {:>7} {:>7}  {:<24}  {}name",
+            "compressed",
+            "size",
+            "ratio",
+            "codec",
+            "type",
+            location_header
+        );
+    } else {
+        say!(
+            "{:>12} {:>12} {:>7}  {:<24}  {}name",
+            "compressed",
+            "size",
+            "codec",
+            "type",
+            location_header
+        );
+    }
+    for asset in &assets {
+        let decompressed = match dumper.decompress_asset(asset) {
+            Ok(decompressed) => decompressed,
+            Err(e) if args.strict => {
+                return Err(
+                    anyhow::Error::new(e).context(format!("failed to decompress {}", asset.name))
+                )
+            }
+            Err(e) => {
+                report_skipped(asset, e, &ProgressBar::hidden());
+                continue;
+            }
+        };
+        manifest.push(asset, &decompressed);
+        let entry = manifest.assets.last().unwrap();
+        let location = if args.offsets {
+            location(asset)
+        } else {
+            String::new()
+        };
 
-        Ok(assets)
+        if args.verbose > 0 {
+            say!(
+                "{:>12} {:>12} {:>7.2} {:>7}  {:<24}  {}{}",
+                asset.data.len(),
+                decompressed.len(),
+                entry.compression_ratio(),
+                asset.codec,
+                entry.mime,
+                location,
+                asset.name
+            )
+        } else {
+            say!(
+                "{:>12} {:>12} {:>7}  {:<24}  {}{}",
+                asset.data.len(),
+                decompressed.len(),
+                asset.codec,
+                entry.mime,
+                location,
+                asset.name
+            )
+        }
+        if let Some(peek) = args.peek {
+            let dump = hexdump(&decompressed[..peek.min(decompressed.len())]);
+            if !dump.is_empty() {
+                say!("{}", dump.trim_end_matches('\n'));
+            }
+        }
+        count += 1;
+        total_compressed += asset.data.len();
+        total_decompressed += decompressed.len();
     }
+    say!(
+        "{:>12} {:>12} {:>w$}  total ({} assets)",
+        total_compressed,
+        total_decompressed,
+        "",
+        count,
+        w = if args.verbose > 0 { 15 } else { 7 } + 26 + location_header.len()
+    );
+
+    Ok(count)
+}
+
+// `--offsets` columns, in hex to paste into a disassembler; only table entries have addresses
+fn location(asset: &Asset) -> String {
+    let hex = |value: Option<u64>| value.map_or("-".to_string(), |value| format!("{:#x}", value));
+    let entry = asset.table_entry.as_ref();
+    format!(
+        "{:>10} {:>18} {:>18} {:>10}  ",
+        hex(entry.map(|e| e.offset)),
+        hex(entry.map(|e| e.name_address)),
+        hex(entry.map(|e| e.data_address)),
+        hex(Some(asset.data_offset))
+    )
+}
+
+// whether the assets are decompressed straight into their files, never held in memory
+fn streams(args: &Args) -> bool {
+    let archived = args.zip.is_some() || args.tar.is_some() || args.json_bundle.is_some();
+    // the line endings of text can only be converted once it is known to be text, and the
+    // content the baseline already has is left out with --changed-only, which can only be
+    // told once it is hashed
+    args.output.is_some()
+        && !args.list
+        && !args.stats_only
+        && !args.dry_run
+        && !args.raw
+        && !args.resume
+        && !archived
+        && args.eol == Eol::Keep
+        && !args.changed_only
+}
+
+fn dump_assets(
+    dumper: &Dumper,
+    args: &Args,
+    filter: &AssetFilter,
+    baseline: Option<&Manifest>,
+    manifest: &mut Manifest,
+    log: &mut AuditLog,
+) -> Result<usize> {
+    let assets = scan_assets(dumper, args, filter)?;
+    let plan = plan_outputs(&assets, args);
 
-    fn parse_asset(&self, offset: usize) -> Result<Asset> {
-        if offset + ASSET_HEADER_SIZE > self.mmap.len() {
-            return Err(anyhow!("offset is out of range"));
+    // symlinks already present in the output directory must not redirect writes elsewhere
+    let root = match &args.output {
+        Some(output) if !args.dry_run => {
+            fs::create_dir_all(output)?;
+            let root = fs::canonicalize(output)?;
+            // other dumps may share the output, only the prefix is this one's to clean
+            let dir = match &args.prefix {
+                Some(prefix) => {
+                    let dir = root.join(prefix);
+                    fs::create_dir_all(&dir)?;
+                    path::ensure_within(&root, &dir)?;
+                    dir
+                }
+                None => root.clone(),
+            };
+            prepare_output(&dir, args)?;
+            Some(root)
         }
+        output => output.as_ref().map(PathBuf::from),
+    };
+    let bar = progress_bar(
+        args,
+        plan.len() as u64,
+        "Dumping  [{bar:40}] {pos}/{len} assets",
+    );
 
-        let chunk = &self.mmap[offset..offset + ASSET_HEADER_SIZE];
+    // archives are written afterwards in a fixed order, they need the content until then
+    let archived = args.zip.is_some() || args.tar.is_some() || args.json_bundle.is_some();
+    let streamed = streams(args);
+    let previous: HashMap<&str, &str> = match baseline {
+        Some(baseline) if args.changed_only => baseline
+            .assets
+            .iter()
+            .map(|entry| (entry.name.as_str(), entry.sha256.as_str()))
+            .collect(),
+        _ => HashMap::new(),
+    };
 
-        let header = unsafe { &*(chunk.as_ptr() as *const AssetHeader) };
+    // decompress and write in parallel, create_dir_all tolerates concurrent creation
+    let results = plan
+        .par_iter()
+        .map(|(asset, relative)| {
+            if let (Some(root), true) = (&root, streamed) {
+                return stream_asset(dumper, asset, root, relative, args, &bar);
+            }
+            let decompressed = match dumper.decompress_asset(asset) {
+                Ok(decompressed) => decompressed,
+                Err(e) if args.strict => {
+                    return Err(anyhow::Error::new(e)
+                        .context(format!("failed to decompress {}", asset.name)))
+                }
+                Err(e) => {
+                    report_skipped(asset, e, &bar);
+                    bar.inc(1);
+                    return Ok(None);
+                }
+            };
+            let plausible = mime::plausible(&asset.name, &decompressed);
+            check_content(asset, decompressed.len(), plausible, args, &bar);
+            let entry = ManifestEntry::new(asset, &decompressed);
+            if previous.get(asset.name.as_str()) == Some(&entry.sha256.as_str()) {
+                bar.inc(1);
+                return Ok(Some((entry, None, Output::Unchanged)));
+            }
+            let content = if args.raw {
+                Cow::Borrowed(asset.data.as_slice())
+            } else {
+                convert_eol(&asset.name, &decompressed, args.eol)
+            };
+            let mut output = Output::Written;
 
-        let name_off = self.convert_rva_to_file_offset(header.name_ptr)?;
-        let data_off = self.convert_rva_to_file_offset(header.data_ptr)?;
+            if let (Some(root), false) = (&root, args.dry_run) {
+                let path = root.join(relative);
+                // create parent directory if not exists
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(path::long_path(parent))?;
+                    path::ensure_within(root, parent)?;
+                }
 
-        if !self.validate_asset_pointers(name_off, header.name_len, data_off, header.data_size) {
-            return Err(anyhow!("invalid asset pointers"));
+                if args.resume && is_complete(&path, &content) {
+                    output = Output::Resumed;
+                } else if let Err(e) = fs::write(path::long_path(&path), &content) {
+                    let e = anyhow::Error::new(e).context(format!("failed to write {:?}", path));
+                    if args.strict {
+                        return Err(e);
+                    }
+                    report_skipped(asset, e, &bar);
+                    bar.inc(1);
+                    return Ok(None);
+                }
+            }
+            bar.inc(1);
+            let kept = if archived && !args.dry_run {
+                Some(match content {
+                    Cow::Owned(converted) => converted,
+                    Cow::Borrowed(_) if args.raw => asset.data.clone(),
+                    Cow::Borrowed(_) => decompressed,
+                })
+            } else {
+                None
+            };
+            Ok(Some((entry, kept, output)))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    bar.finish_and_clear();
+
+    let mut dumped = Vec::new();
+    let mut entries = Vec::new();
+    let mut contents = Vec::new();
+    let mut skipped = Vec::new();
+    // every asset read, written or not, for the manifest
+    let mut recorded = Vec::new();
+    for ((asset, relative), result) in plan.iter().zip(results) {
+        match result {
+            Some((entry, _, Output::Unchanged)) => recorded.push(entry),
+            Some((entry, kept, output)) => {
+                dumped.push((*asset, relative, output == Output::Resumed));
+                recorded.push(entry.clone());
+                entries.push(entry);
+                contents.push(kept);
+            }
+            None => skipped.push(asset.name.as_str()),
         }
+    }
 
-        let name = self.retrieve_asset_name(name_off as usize, header.name_len as usize)?;
-        let data = self.retrieve_asset_data(data_off as usize, header.data_size as usize)?;
+    let files = || {
+        dumped
+            .iter()
+            .zip(&contents)
+            .filter_map(|((_, relative, _), data)| Some((archive_name(relative), data.as_deref()?)))
+            .collect::<Vec<_>>()
+    };
+    if let (Some(path), false) = (&args.zip, args.dry_run) {
+        write_zip(path, files())?;
+    }
+    if let (Some(path), false) = (&args.tar, args.dry_run) {
+        write_tar(path, files())?;
+    }
+    if let (Some(path), false) = (&args.json_bundle, args.dry_run) {
+        let assets = dumped
+            .iter()
+            .zip(&contents)
+            .filter_map(|((asset, _, _), data)| Some((asset.name.as_str(), data.as_deref()?)))
+            .collect();
+        write_json_bundle(path, assets)?;
+    }
 
-        Ok(Asset { name, data })
+    for ((asset, relative, resumed), entry) in dumped.iter().zip(&entries) {
+        let action = if args.dry_run {
+            "Would dump asset"
+        } else if *resumed {
+            "Keep asset"
+        } else {
+            "Dump asset"
+        };
+        let details = if args.verbose > 0 {
+            format!(
+                ", decompressed: {:#X}, ratio: {:.2}",
+                entry.decompressed_size,
+                entry.compression_ratio()
+            )
+        } else {
+            String::new()
+        };
+        say!(
+            "{}: {}, size: {}, codec: {}{}",
+            action,
+            asset
+                .name
+                .if_supports_color(Stream::Stdout, |text| text.bold()),
+            format!("{:#X}", asset.data.len())
+                .if_supports_color(Stream::Stdout, |text| text.cyan()),
+            asset.codec,
+            details
+        );
+        if !args.dry_run {
+            emit(Event::AssetWritten {
+                name: &asset.name,
+                path: archive_name(relative),
+                decompressed_size: entry.decompressed_size,
+                sha256: &entry.sha256,
+                resumed: *resumed,
+            });
+        }
+        log.line(&format!(
+            "{}: {}, offset: {:#X}, size: {:#X}, codec: {}, decompressed: {:#X}, sha256: {}, to: {}",
+            action,
+            asset.name,
+            asset.data_offset,
+            asset.data.len(),
+            asset.codec,
+            entry.decompressed_size,
+            entry.sha256,
+            archive_name(relative)
+        ))?;
+    }
+    dedup_assets(&dumped, &entries, root.as_deref(), args)?;
+    if args.dry_run {
+        say!(
+            "Dry run: {} assets, {} bytes would be written to {}",
+            entries.len(),
+            entries.iter().map(|e| e.decompressed_size).sum::<usize>(),
+            [&args.output, &args.zip, &args.tar, &args.json_bundle]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(" and ")
+        );
     }
+    if !skipped.is_empty() {
+        let message = format!(
+            "Skipped {} assets that failed to decompress or write: {}",
+            skipped.len(),
+            skipped.join(", ")
+        );
+        eprintln!(
+            "{}",
+            message.if_supports_color(Stream::Stderr, |text| text.yellow())
+        );
+    }
+    let resumed = dumped.iter().filter(|(_, _, resumed)| *resumed).count();
+    if resumed > 0 {
+        say!(
+            "Resumed: kept {} assets already in the output directory",
+            resumed
+        );
+    }
+    let unchanged = recorded.len() - dumped.len();
+    if unchanged > 0 {
+        say!(
+            "Unchanged: left out {} assets the baseline already has",
+            unchanged
+        );
+    }
+    let count = recorded.len();
+    manifest.assets.extend(recorded);
+
+    Ok(count)
+}
 
-    fn validate_asset_pointers(
-        &self,
-        name_ptr: u64,
-        name_len: u64,
-        data_ptr: u64,
-        data_size: u64,
-    ) -> bool {
-        let name_offset = name_ptr as usize;
-        let data_offset = data_ptr as usize;
+// the manifest entry of a dumped asset, its content when kept for the archives, and what
+// became of its file
+type Dumped = (ManifestEntry, Option<Vec<u8>>, Output);
 
-        // check if pointers are in the file range
-        if name_offset >= self.mmap.len()
-            || name_offset.saturating_add(name_len as usize) > self.mmap.len()
-            || data_offset >= self.mmap.len()
-            || data_offset.saturating_add(data_size as usize) > self.mmap.len()
-        {
-            return false;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Written,
+    // by an earlier run, see --resume
+    Resumed,
+    // left out, the baseline has the same content, see --changed-only
+    Unchanged,
+}
+
+// `content` of the asset served as `name` with its line endings made `eol`, unless it is
+// not text; lone `\r`s are left alone
+fn convert_eol<'a>(name: &str, content: &'a [u8], eol: Eol) -> Cow<'a, [u8]> {
+    let text = mime::is_text(mime::guess(name, content)) && std::str::from_utf8(content).is_ok();
+    if eol == Eol::Keep || !text {
+        return Cow::Borrowed(content);
+    }
+    let mut converted = Vec::with_capacity(content.len());
+    for (i, &byte) in content.iter().enumerate() {
+        if byte == b'\r' && content.get(i + 1) == Some(&b'\n') {
+            continue;
+        }
+        if byte == b'\n' && eol == Eol::Crlf {
+            converted.push(b'\r');
         }
+        converted.push(byte);
+    }
+    Cow::Owned(converted)
+}
 
-        // check name format
-        if self.mmap[name_offset] != b'/' {
-            return false;
+// decompresses `asset` into its file under `root`, taking what the manifest needs on the way
+fn stream_asset(
+    dumper: &Dumper,
+    asset: &Asset,
+    root: &Path,
+    relative: &Path,
+    args: &Args,
+    bar: &ProgressBar,
+) -> Result<Option<Dumped>> {
+    let path = root.join(relative);
+    // create parent directory if not exists
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(path::long_path(parent))?;
+        path::ensure_within(root, parent)?;
+    }
+
+    let written = File::create(path::long_path(&path))
+        .map_err(DumperError::from)
+        .and_then(|file| {
+            let mut digest = DigestWriter::new(BufWriter::new(file));
+            dumper.decompress_asset_to(asset, &mut digest)?;
+            digest.flush()?;
+            Ok(digest)
+        });
+    let digest = match written {
+        Ok(digest) => digest,
+        Err(e) => {
+            // part of the content is worse than none
+            let _ = fs::remove_file(path::long_path(&path));
+            let e = match e {
+                DumperError::Io(e) => {
+                    anyhow::Error::new(e).context(format!("failed to write {:?}", path))
+                }
+                e if args.strict => {
+                    anyhow::Error::new(e).context(format!("failed to decompress {}", asset.name))
+                }
+                e => anyhow::Error::new(e),
+            };
+            if args.strict {
+                return Err(e);
+            }
+            report_skipped(asset, e, bar);
+            bar.inc(1);
+            return Ok(None);
         }
+    };
+    let plausible = mime::plausible_head(&asset.name, digest.head(), digest.is_utf8());
+    check_content(asset, digest.size(), plausible, args, bar);
+    bar.inc(1);
+    Ok(Some((
+        ManifestEntry::from_digest(asset, &digest),
+        None,
+        Output::Written,
+    )))
+}
 
-        // check brotli decompression
-        let mut decompressor = brotli::Decompressor::new(
-            &self.mmap[data_offset..data_offset + data_size as usize],
-            data_size as usize,
+// warns about decompressed content of `size` bytes that does not look like an asset
+fn check_content(asset: &Asset, size: usize, plausible: bool, args: &Args, bar: &ProgressBar) {
+    if !compressed(asset, size) {
+        bar.suspend(|| {
+            eprintln!(
+                "{} {} does not shrink when compressed ({} -> {} bytes), check it is an asset",
+                warning(),
+                asset.name,
+                size,
+                asset.data.len()
+            )
+        });
+    }
+    if !plausible {
+        bar.suspend(|| {
+            eprintln!(
+                "{} {} decodes to content that does not fit its extension, {}",
+                warning(),
+                asset.name,
+                // brotli streams that need a dictionary fail to decode and pass as raw
+                if args.brotli_dict.is_none() {
+                    "it may be brotli compressed against a dictionary, see --brotli-dict"
+                } else {
+                    "check it is an asset"
+                }
+            )
+        });
+    }
+}
+
+// finds the assets with the same content as an earlier one, replacing their files with
+// symlinks to the first copy under --dedup
+fn dedup_assets(
+    dumped: &[(&Asset, &PathBuf, bool)],
+    entries: &[ManifestEntry],
+    root: Option<&Path>,
+    args: &Args,
+) -> Result<()> {
+    let mut first: HashMap<String, &PathBuf> = HashMap::new();
+    let (mut duplicates, mut bytes) = (0, 0);
+    for ((asset, relative, _), entry) in dumped.iter().zip(entries) {
+        let (hash, size) = if args.raw {
+            (sha256_hex(&asset.data), asset.data.len())
+        } else {
+            (entry.sha256.clone(), entry.decompressed_size)
+        };
+        // nothing to save on empty files
+        if size == 0 {
+            continue;
+        }
+        let Some(original) = first.get(&hash) else {
+            first.insert(hash, relative);
+            continue;
+        };
+        duplicates += 1;
+        bytes += size;
+        if !args.dedup {
+            continue;
+        }
+        let linked = match root {
+            Some(root) if !args.dry_run => link_duplicate(root, original, relative)?,
+            _ => false,
+        };
+        if !linked {
+            say!(
+                "Duplicate asset: {}, same content as {}",
+                archive_name(relative),
+                archive_name(original)
+            );
+        }
+    }
+    if duplicates > 0 {
+        let hint = if args.dedup {
+            ""
+        } else {
+            ", pass --dedup to write them once"
+        };
+        eprintln!(
+            "Note: {} assets duplicate the content of others ({} bytes){}",
+            duplicates, bytes, hint
+        );
+    }
+    Ok(())
+}
+
+// replaces the file at `relative` with a symlink to `original`, both under `root`; the link
+// is relative so the output directory can be moved
+#[cfg(unix)]
+fn link_duplicate(root: &Path, original: &Path, relative: &Path) -> Result<bool> {
+    let depth = relative.parent().map_or(0, |dir| dir.components().count());
+    let target = std::iter::repeat_n(Component::ParentDir.as_os_str(), depth)
+        .collect::<PathBuf>()
+        .join(original);
+    let path = root.join(relative);
+    fs::remove_file(&path).with_context(|| format!("failed to remove {:?}", path))?;
+    std::os::unix::fs::symlink(&target, &path)
+        .with_context(|| format!("failed to link {:?} to {:?}", path, target))?;
+    Ok(true)
+}
+
+// symlinks need special privileges on Windows, the duplicates are only reported
+#[cfg(not(unix))]
+fn link_duplicate(_root: &Path, _original: &Path, _relative: &Path) -> Result<bool> {
+    Ok(false)
+}
+
+// an earlier, interrupted run left `content` at `path`; a file cut short has the wrong size
+fn is_complete(path: &Path, content: &[u8]) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() == content.len() as u64)
+}
+
+// empties the output directory with --clean, otherwise warns when a previous dump may linger
+fn prepare_output(root: &Path, args: &Args) -> Result<()> {
+    let mut entries = fs::read_dir(root)?.peekable();
+    if entries.peek().is_none() {
+        return Ok(());
+    }
+    if args.resume {
+        return Ok(());
+    }
+    if !args.clean {
+        eprintln!(
+            "{} output directory {} is not empty, files from earlier runs may be \
+             overwritten or left behind (use --clean to empty it first)",
+            warning(),
+            root.display()
         );
-        let mut decompressed = Vec::new();
-        decompressor.read_to_end(&mut decompressed).is_ok()
+        return Ok(());
+    }
+    if root.parent().is_none() {
+        return Err(anyhow!("refusing to clean {}", root.display()));
+    }
+    for entry in entries {
+        let entry = entry?;
+        // symlinks are removed, never followed
+        if entry.file_type()?.is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        } else {
+            fs::remove_file(entry.path())?;
+        }
     }
+    say!("Cleaned {}", root.display());
+    Ok(())
+}
 
-    fn retrieve_asset_name(&self, offset: usize, len: usize) -> Result<String> {
-        let name = self.mmap[offset..offset + len].to_vec();
-        if !name.iter().all(|&b| b.is_ascii()) {
-            return Err(anyhow!("invalid name"));
+// output path of the asset, relative to the output directory or archive root
+fn output_path(asset: &Asset, index: usize, args: &Args) -> Result<PathBuf> {
+    // `tauri://localhost/index.html` is written to `index.html`
+    let name = asset
+        .name
+        .strip_prefix(args.name_prefix.as_str())
+        .unwrap_or(&asset.name);
+    let mut path = if args.as_dist {
+        path::relative_path(&path::dist_name(name))?
+    } else {
+        path::relative_path(name)?
+    };
+    if args.flat {
+        path = path.file_name().map(PathBuf::from).unwrap_or_default();
+    }
+    if let Some(template) = &args.name_template {
+        let lossy = |part: Option<&std::ffi::OsStr>| {
+            part.unwrap_or_default().to_string_lossy().into_owned()
+        };
+        let name = expand_template(template, |field| match field {
+            "path" => archive_name(&path),
+            "dir" => archive_name(path.parent().unwrap_or(Path::new(""))),
+            "name" => lossy(path.file_name()),
+            "stem" => lossy(path.file_stem()),
+            "ext" => lossy(path.extension()),
+            "index" => index.to_string(),
+            _ => sha256_hex(asset.name.as_bytes())[..16].to_string(),
+        })
+        .map_err(|e| anyhow!(e))?;
+        // the template is trusted no more than the asset names
+        path = path::relative_path(&name)?;
+        if path.file_name().is_none() {
+            return Err(anyhow!("--name-template gives no file name: {:?}", name));
         }
-        let name = String::from_utf8(name)?;
+    }
+    // `index.html` -> `index.html.br`, so the codec stays obvious
+    if let Some(extension) = asset.codec.extension().filter(|_| args.raw) {
+        let mut name = path.into_os_string();
+        name.push(".");
+        name.push(extension);
+        path = name.into();
+    }
+    if let Some(prefix) = &args.prefix {
+        path = prefix.join(path);
+    }
 
-        Ok(name)
+    if cfg!(windows) || args.portable_names {
+        path = path::portable_path(&path, &args.replace_invalid);
     }
 
-    fn retrieve_asset_data(&self, offset: usize, len: usize) -> Result<Vec<u8>> {
-        Ok(self.mmap[offset..offset + len].to_vec())
+    // sanitize path
+    if path.is_absolute() || path.normalize().starts_with("..") {
+        return Err(anyhow!("Path traversal found: {:?}", path));
     }
 
-    fn decompress_asset(&self, asset: &Asset) -> Result<Vec<u8>> {
-        let reader = Cursor::new(&asset.data);
-        let mut decompressor = brotli::Decompressor::new(reader, asset.data.len());
-        let mut decompressed = Vec::new();
-        decompressor.read_to_end(&mut decompressed)?;
-        Ok(decompressed)
+    Ok(path)
+}
+
+// `xxd`-like: offset, 16 bytes in hex, then the printable ones; indented below the listing
+fn hexdump(data: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, bytes) in data.chunks(16).enumerate() {
+        let hex: Vec<_> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        let text: String = bytes
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        dump += &format!("    {:08x}  {:<47}  |{}|\n", line * 16, hex.join(" "), text);
+    }
+    dump
+}
+
+// entries follow the `--sort` order
+fn write_zip(path: &str, files: Vec<(String, &[u8])>) -> Result<()> {
+    let mut zip = zip::ZipWriter::new(File::create(path)?);
+    // the content is either decompressed already or compressed, deflating buys little
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Stored)
+        .unix_permissions(0o644);
+    for (name, data) in files {
+        zip.start_file(name, options)?;
+        zip.write_all(data)?;
     }
+    zip.finish()?;
+    Ok(())
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+// entries are sorted and carry no timestamps or owners, so the same assets give the same bytes
+fn write_tar(path: &str, mut files: Vec<(String, &[u8])>) -> Result<()> {
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let gz = flate2::write::GzEncoder::new(File::create(path)?, flate2::Compression::default());
+    let mut builder = tar::Builder::new(gz);
+    let mut dirs = BTreeSet::new();
+    for (name, data) in files {
+        // emit each missing parent directory right before its first file
+        let mut parents: Vec<_> = name.match_indices('/').map(|(i, _)| &name[..=i]).collect();
+        parents.retain(|dir| !dirs.contains(*dir));
+        for dir in parents {
+            let mut header = tar_header(tar::EntryType::Directory, 0o755, 0);
+            builder.append_data(&mut header, dir, std::io::empty())?;
+            dirs.insert(dir.to_string());
+        }
 
-    let file = File::open(&args.input)?;
+        let mut header = tar_header(tar::EntryType::Regular, 0o644, data.len() as u64);
+        builder.append_data(&mut header, &name, data)?;
+    }
+    builder.into_inner()?.finish()?;
 
-    let dumper = Dumper::new(file)?;
+    Ok(())
+}
 
-    println!("Scanning for assets...");
-    let assets = dumper.heuristic_search_assets()?;
-    println!("Scanning completed. Found {} assets", assets.len());
+// entries follow the `--sort` order, named after the asset rather than the output path
+fn write_json_bundle(path: &str, assets: Vec<(&str, &[u8])>) -> Result<()> {
+    const LARGE_BUNDLE: usize = 100 * 1024 * 1024;
 
-    if assets.is_empty() {
-        return Err(anyhow!("No assets found"));
+    #[derive(Serialize)]
+    struct BundleEntry<'a> {
+        name: &'a str,
+        size: usize,
+        data_base64: String,
     }
 
-    // dump assets
-    for asset in assets {
-        let decompressed = dumper.decompress_asset(&asset)?;
+    let total = assets.iter().map(|(_, data)| data.len()).sum::<usize>();
+    if total > LARGE_BUNDLE {
+        eprintln!(
+            "{} {} bytes of assets go into {}, base64 makes that a third larger",
+            warning(),
+            total,
+            path
+        );
+    }
+    let entries: Vec<_> = assets
+        .into_iter()
+        .map(|(name, data)| BundleEntry {
+            name,
+            size: data.len(),
+            data_base64: BASE64_STANDARD.encode(data),
+        })
+        .collect();
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, &entries)?;
+    writer.flush()?;
+    Ok(())
+}
 
-        // remove starts with /
-        let path = Path::new(&args.output).join(&asset.name[1..]);
+fn tar_header(kind: tar::EntryType, mode: u32, size: u64) -> tar::Header {
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(kind);
+    header.set_mode(mode);
+    header.set_size(size);
+    header.set_mtime(0);
+    header.set_uid(0);
+    header.set_gid(0);
+    header
+}
 
-        // sanitize path
-        if !path.normalize().starts_with(&args.output) {
-            return Err(anyhow!("Path traversal found: {:?}", path));
+// archive entries always use `/`, whatever the host separator is
+fn archive_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// resolve every output path up front, so the parallel writes never race on one file
+fn plan_outputs<'a>(assets: &'a [Asset], args: &Args) -> Vec<(&'a Asset, PathBuf)> {
+    let mut plan: Vec<(&Asset, PathBuf)> = Vec::new();
+    let mut taken = HashMap::new();
+
+    for (index, asset) in assets.iter().enumerate() {
+        let path = match output_path(asset, index, args) {
+            Ok(path) => path,
+            Err(e) => {
+                eprintln!("{} skipping {:?}: {}", warning(), asset.name, e);
+                emit(Event::Error {
+                    message: e.to_string(),
+                    asset: Some(&asset.name),
+                    exit_code: None,
+                });
+                continue;
+            }
+        };
+        let Some(&index) = taken.get(&path) else {
+            taken.insert(path.clone(), plan.len());
+            plan.push((asset, path));
+            continue;
+        };
+
+        // flattening makes unrelated assets collide, keep every one of them
+        let flattened = args.flat && plan[index].0.name != asset.name;
+
+        // same content under the same name, nothing to decide
+        if !flattened && plan[index].0.data == asset.data {
+            continue;
         }
 
-        // create parent directory if not exists
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
+        let policy = if flattened {
+            ConflictPolicy::Rename
+        } else {
+            eprintln!(
+                "{} {} is claimed by several assets with different data, applying --on-conflict {}",
+                warning(),
+                asset.name,
+                args.on_conflict.to_possible_value().unwrap().get_name()
+            );
+            args.on_conflict
+        };
+        match policy {
+            ConflictPolicy::Skip => {}
+            ConflictPolicy::Overwrite => plan[index].0 = asset,
+            ConflictPolicy::Rename => {
+                let path = (1..)
+                    .map(|n| renamed_path(&path, n))
+                    .find(|p| !taken.contains_key(p))
+                    .unwrap();
+                taken.insert(path.clone(), plan.len());
+                plan.push((asset, path));
+            }
+        }
+    }
+
+    plan
+}
+
+// `dir/index.html` -> `dir/index-1.html`
+fn renamed_path(path: &Path, n: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, n, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, n),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(extra: &[&str]) -> Args {
+        Args::parse_from(
+            ["tauri-dumper", "--input", "app", "--output", "out"]
+                .iter()
+                .chain(extra),
+        )
+    }
+
+    fn asset(name: &str, data: &[u8]) -> Asset {
+        Asset {
+            name: name.to_string(),
+            data: data.to_vec(),
+            data_offset: 0,
+            table_entry: None,
+            codec: Codec::Identity,
+            decompressed: None,
         }
+    }
 
-        println!("Dump asset: {}, size: {:#X}", asset.name, asset.data.len());
-        fs::write(path, decompressed)?;
+    #[test]
+    fn output_paths_stay_in_the_output_directory() {
+        let names = [
+            "/../../x",
+            "..\\x",
+            "a/../../x",
+            "C:\\x",
+            "\\\\server\\share",
+            "a\0b",
+        ];
+        let layouts: [&[&str]; 5] = [
+            &[],
+            &["--as-dist"],
+            &["--flat"],
+            &["--raw"],
+            &["--name-template", "{dir}/{name}"],
+        ];
+        for layout in layouts {
+            let args = args(layout);
+            for name in names {
+                match output_path(&asset(name, b"content"), 0, &args) {
+                    Ok(path) => assert!(
+                        path.components().all(|c| matches!(c, Component::Normal(_))),
+                        "{name:?} {layout:?}: {path:?}"
+                    ),
+                    Err(e) => assert!(
+                        !matches!(name, "C:\\x" | "\\\\server\\share") || cfg!(windows),
+                        "{name:?} {layout:?}: {e}"
+                    ),
+                }
+            }
+            for name in ["/../../x", "..\\x", "a/../../x", "a\0b"] {
+                assert!(
+                    output_path(&asset(name, b"content"), 0, &args).is_err(),
+                    "{name:?}"
+                );
+            }
+        }
     }
 
-    println!("Done :)");
+    #[test]
+    fn streamed_dumps() {
+        assert!(streams(&args(&[])));
+        let buffered: [&[&str]; 6] = [
+            &["--raw"],
+            &["--dry-run"],
+            &["--resume"],
+            &["--zip", "out.zip"],
+            &["--eol", "lf"],
+            &[
+                "--manifest",
+                "m.json",
+                "--baseline",
+                "m.json",
+                "--changed-only",
+            ],
+        ];
+        for extra in buffered {
+            assert!(!streams(&args(extra)), "{extra:?}");
+        }
+    }
 
-    Ok(())
+    #[test]
+    fn name_templates() {
+        let field = |name: &str| format!("<{name}>");
+        assert_eq!(
+            expand_template("{index}-{hash}.{ext}", field).unwrap(),
+            "<index>-<hash>.<ext>"
+        );
+        assert_eq!(
+            expand_template("assets/app.js", field).unwrap(),
+            "assets/app.js"
+        );
+        let unknown = expand_template("{dir}/{size}", field).unwrap_err();
+        assert!(
+            unknown.starts_with("unknown placeholder {size}"),
+            "{unknown}"
+        );
+        assert_eq!(
+            expand_template("{name", field).unwrap_err(),
+            "unclosed '{' in template"
+        );
+    }
+
+    #[test]
+    fn line_endings() {
+        let text = b"a\r\nb\nc\rd";
+        assert_eq!(&*convert_eol("/app.js", text, Eol::Lf), b"a\nb\nc\rd");
+        assert_eq!(&*convert_eol("/app.js", text, Eol::Crlf), b"a\r\nb\r\nc\rd");
+        assert!(matches!(
+            convert_eol("/app.js", text, Eol::Keep),
+            Cow::Borrowed(_)
+        ));
+        // binary content is left alone, whatever its name says
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(&*convert_eol("/logo.png", png, Eol::Lf), png);
+        assert_eq!(&*convert_eol("/app.js", b"\xff\r\n", Eol::Lf), b"\xff\r\n");
+    }
+
+    #[test]
+    fn clashing_outputs() {
+        assert_eq!(
+            renamed_path(Path::new("dir/index.html"), 1),
+            Path::new("dir/index-1.html")
+        );
+        assert_eq!(
+            renamed_path(Path::new("LICENSE"), 2),
+            Path::new("LICENSE-2")
+        );
+
+        let assets = [
+            asset("/index.html", b"one"),
+            asset("/index.html", b"one"),
+            asset("/index.html", b"two"),
+            asset("/a/app.js", b"a"),
+            asset("/b/app.js", b"b"),
+        ];
+        let planned = |extra: &[&str]| -> Vec<(String, PathBuf)> {
+            plan_outputs(&assets, &args(extra))
+                .into_iter()
+                .map(|(asset, path)| (String::from_utf8(asset.data.clone()).unwrap(), path))
+                .collect()
+        };
+        let plan = |outputs: &[(&str, &str)]| -> Vec<(String, PathBuf)> {
+            outputs
+                .iter()
+                .map(|(data, path)| (data.to_string(), PathBuf::from(path)))
+                .collect()
+        };
+        // the same content twice is written once, whatever the policy
+        assert_eq!(
+            planned(&[]),
+            plan(&[("two", "index.html"), ("a", "a/app.js"), ("b", "b/app.js")])
+        );
+        assert_eq!(
+            planned(&["--on-conflict", "skip"]),
+            plan(&[("one", "index.html"), ("a", "a/app.js"), ("b", "b/app.js")])
+        );
+        assert_eq!(
+            planned(&["--on-conflict", "rename"]),
+            plan(&[
+                ("one", "index.html"),
+                ("two", "index-1.html"),
+                ("a", "a/app.js"),
+                ("b", "b/app.js")
+            ])
+        );
+        // flattening never drops an asset for another one
+        assert_eq!(
+            planned(&["--flat"]),
+            plan(&[("two", "index.html"), ("a", "app.js"), ("b", "app-1.js")])
+        );
+    }
+
+    #[test]
+    fn hexdump_lines() {
+        assert_eq!(
+            hexdump(b"<html>\n\0hello tauri, again"),
+            "    00000000  3c 68 74 6d 6c 3e 0a 00 68 65 6c 6c 6f 20 74 61  |<html>..hello ta|\n\
+             \x20   00000010  75 72 69 2c 20 61 67 61 69 6e                    |uri, again|\n"
+        );
+        assert_eq!(hexdump(b""), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn duplicates_become_symlinks() {
+        let root = std::env::temp_dir().join(format!("tauri-dumper-dedup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let assets = [
+            asset("/index.html", b"same"),
+            asset("/assets/copy.html", b"same"),
+            asset("/other.html", b"other"),
+        ];
+        let paths = ["index.html", "assets/copy.html", "other.html"].map(PathBuf::from);
+        for (asset, path) in assets.iter().zip(&paths) {
+            fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+            fs::write(root.join(path), &asset.data).unwrap();
+        }
+        let dumped: Vec<_> = assets
+            .iter()
+            .zip(&paths)
+            .map(|(a, p)| (a, p, false))
+            .collect();
+        let entries: Vec<_> = assets
+            .iter()
+            .map(|asset| ManifestEntry::new(asset, &asset.data))
+            .collect();
+
+        // only reported without --dedup
+        dedup_assets(&dumped, &entries, Some(&root), &args(&[])).unwrap();
+        assert!(!root.join("assets/copy.html").is_symlink());
+
+        dedup_assets(&dumped, &entries, Some(&root), &args(&["--dedup"])).unwrap();
+        let copy = root.join("assets/copy.html");
+        assert_eq!(fs::read_link(&copy).unwrap(), Path::new("../index.html"));
+        assert_eq!(fs::read(&copy).unwrap(), b"same");
+        assert!(!root.join("index.html").is_symlink());
+        assert!(!root.join("other.html").is_symlink());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn exit_codes() {
+        for failure in [
+            Failure::Unsupported,
+            Failure::NoAssets,
+            Failure::VerifyFailed,
+            Failure::TimedOut,
+        ] {
+            assert_eq!(exit_code(&failure.into()), failure as u8);
+            // also when explaining the error underneath
+            assert_eq!(
+                exit_code(&anyhow!("details").context(failure)),
+                failure as u8
+            );
+        }
+        let missing = io::Error::new(io::ErrorKind::NotFound, "missing");
+        assert_eq!(
+            exit_code(&anyhow::Error::new(missing).context("failed to read app")),
+            4
+        );
+        assert_eq!(
+            exit_code(&DumperError::Io(io::Error::other("full")).into()),
+            4
+        );
+        assert_eq!(exit_code(&anyhow!("anything else")), 1);
+        for code in 0..=6 {
+            assert!(EXIT_CODES.contains(&format!("\n  {code}  ")), "{code}");
+        }
+    }
 }
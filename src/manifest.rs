@@ -0,0 +1,284 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::{mime, Asset, Result, TableEntry};
+
+/// Machine readable index of a dump, written by `--manifest`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Whether the dumped files hold the embedded bytes as-is (`--raw`) rather than the
+    /// decompressed content. Hashes and sizes always describe the decompressed content.
+    #[serde(default)]
+    pub raw: bool,
+    pub assets: Vec<ManifestEntry>,
+}
+
+/// One asset in a [`Manifest`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub name: String,
+    pub codec: String,
+    /// Content type guessed from the name, or failing that the content.
+    #[serde(default)]
+    pub mime: String,
+    pub compressed_size: usize,
+    pub decompressed_size: usize,
+    /// Hex encoded SHA-256 of the decompressed bytes.
+    pub sha256: String,
+    /// Offset of the embedded bytes in the binary.
+    #[serde(default)]
+    pub data_offset: u64,
+    /// Where the asset table lists the asset, see [`Asset::table_entry`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub table_entry: Option<TableEntry>,
+}
+
+impl ManifestEntry {
+    pub fn new(asset: &Asset, decompressed: &[u8]) -> Self {
+        Self {
+            name: asset.name.clone(),
+            codec: asset.codec.to_string(),
+            mime: mime::guess(&asset.name, decompressed).to_string(),
+            compressed_size: asset.data.len(),
+            decompressed_size: decompressed.len(),
+            sha256: sha256_hex(decompressed),
+            data_offset: asset.data_offset,
+            table_entry: asset.table_entry,
+        }
+    }
+
+    /// Like [`ManifestEntry::new`], for content written through `digest` and not kept.
+    pub fn from_digest<W>(asset: &Asset, digest: &DigestWriter<W>) -> Self {
+        Self {
+            name: asset.name.clone(),
+            codec: asset.codec.to_string(),
+            mime: mime::guess_head(&asset.name, digest.head(), digest.is_utf8()).to_string(),
+            compressed_size: asset.data.len(),
+            decompressed_size: digest.size(),
+            sha256: digest.sha256_hex(),
+            data_offset: asset.data_offset,
+            table_entry: asset.table_entry,
+        }
+    }
+
+    /// Decompressed size over compressed size, `1.0` for stored assets.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.compressed_size == 0 {
+            return 1.0;
+        }
+        self.decompressed_size as f64 / self.compressed_size as f64
+    }
+}
+
+impl Manifest {
+    /// Records `asset` along with its decompressed contents.
+    pub fn push(&mut self, asset: &Asset, decompressed: &[u8]) {
+        self.assets.push(ManifestEntry::new(asset, decompressed));
+    }
+
+    /// Writes the manifest as pretty-printed JSON.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        Ok(fs::write(path, json)?)
+    }
+
+    /// Reads a manifest previously written by [`Manifest::write`].
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let json = fs::read(path)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+
+    /// Compares the assets against `expected` by name and SHA-256, sorted by name.
+    pub fn verify(&self, expected: &Manifest) -> Vec<Difference> {
+        let actual = Self::digests(self);
+        let expected = Self::digests(expected);
+
+        let mut differences = Vec::new();
+        for (&name, &sha256) in &expected {
+            match actual.get(name) {
+                None => differences.push(Difference::Missing(name.to_string())),
+                Some(&found) if found != sha256 => differences.push(Difference::Changed {
+                    name: name.to_string(),
+                    expected: sha256.to_string(),
+                    actual: found.to_string(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for &name in actual.keys().filter(|name| !expected.contains_key(*name)) {
+            differences.push(Difference::Extra(name.to_string()));
+        }
+        differences.sort_by(|a, b| a.name().cmp(b.name()));
+        differences
+    }
+
+    fn digests(&self) -> BTreeMap<&str, &str> {
+        self.assets
+            .iter()
+            .map(|e| (e.name.as_str(), e.sha256.as_str()))
+            .collect()
+    }
+}
+
+/// A way a dump differs from the manifest it is verified against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// In the manifest, but not in the dump.
+    Missing(String),
+    /// In the dump, but not in the manifest.
+    Extra(String),
+    /// In both, with different content.
+    Changed {
+        name: String,
+        expected: String,
+        actual: String,
+    },
+}
+
+impl Difference {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Missing(name) | Self::Extra(name) | Self::Changed { name, .. } => name,
+        }
+    }
+}
+
+/// Aggregate statistics over a [`Manifest`].
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub assets: usize,
+    pub compressed_bytes: usize,
+    pub decompressed_bytes: usize,
+    /// Decompressed bytes over compressed bytes.
+    pub compression_ratio: f64,
+    /// Largest assets by decompressed size, biggest first.
+    pub largest: Vec<ManifestEntry>,
+}
+
+impl Summary {
+    /// Summarizes `manifest`, keeping the `top` largest assets.
+    pub fn new(manifest: &Manifest, top: usize) -> Self {
+        let compressed_bytes = manifest.assets.iter().map(|e| e.compressed_size).sum();
+        let decompressed_bytes = manifest.assets.iter().map(|e| e.decompressed_size).sum();
+
+        let mut largest = manifest.assets.clone();
+        largest.sort_by_key(|e| std::cmp::Reverse(e.decompressed_size));
+        largest.truncate(top);
+
+        Self {
+            assets: manifest.assets.len(),
+            compressed_bytes,
+            decompressed_bytes,
+            compression_ratio: if compressed_bytes == 0 {
+                1.0
+            } else {
+                decompressed_bytes as f64 / compressed_bytes as f64
+            },
+            largest,
+        }
+    }
+
+    /// Writes the summary as pretty-printed JSON.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        Ok(fs::write(path, json)?)
+    }
+}
+
+/// Hex encoded SHA-256 digest of `data`.
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Passes content on to `inner` while taking what a [`ManifestEntry`] records of it: the size,
+/// the hash, the first [`mime::HEAD_LEN`] bytes and whether it is UTF-8. Content streamed to
+/// a file this way never has to be held in memory.
+pub struct DigestWriter<W> {
+    inner: W,
+    hasher: Sha256,
+    size: usize,
+    head: Vec<u8>,
+    utf8: bool,
+    // the start of a character the last write cut short
+    pending: Vec<u8>,
+}
+
+impl<W> DigestWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            size: 0,
+            head: Vec::new(),
+            utf8: true,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Bytes written so far.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Up to the first [`mime::HEAD_LEN`] bytes written.
+    pub fn head(&self) -> &[u8] {
+        &self.head
+    }
+
+    /// Whether the bytes written so far are valid UTF-8.
+    pub fn is_utf8(&self) -> bool {
+        self.utf8 && self.pending.is_empty()
+    }
+
+    /// Hex encoded SHA-256 of the bytes written so far.
+    pub fn sha256_hex(&self) -> String {
+        hex(&self.hasher.clone().finalize())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn check_utf8(&mut self, buf: &[u8]) {
+        if !self.utf8 {
+            return;
+        }
+        let joined;
+        let data = if self.pending.is_empty() {
+            buf
+        } else {
+            joined = [&self.pending[..], buf].concat();
+            &joined
+        };
+        match std::str::from_utf8(data) {
+            Ok(_) => self.pending.clear(),
+            Err(e) if e.error_len().is_none() => self.pending = data[e.valid_up_to()..].to_vec(),
+            Err(_) => self.utf8 = false,
+        }
+    }
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        let buf = &buf[..written];
+        self.hasher.update(buf);
+        self.size += written;
+        let missing = mime::HEAD_LEN.saturating_sub(self.head.len());
+        self.head.extend_from_slice(&buf[..missing.min(written)]);
+        self.check_utf8(buf);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -0,0 +1,124 @@
+//! Content type guessing for recovered assets.
+//!
+//! Tauri does not embed content types, it derives them from the asset path at runtime, so
+//! this does the same and falls back to sniffing the content.
+
+/// How many bytes of the content [`guess_head`] and [`plausible_head`] look at.
+pub const HEAD_LEN: usize = 512;
+
+/// Guesses the MIME type of the asset served as `name` with the decompressed `content`.
+pub fn guess(name: &str, content: &[u8]) -> &'static str {
+    guess_head(name, content, std::str::from_utf8(content).is_ok())
+}
+
+/// Like [`guess`], for content only known by its first [`HEAD_LEN`] bytes and whether all of
+/// it is UTF-8.
+pub fn guess_head(name: &str, head: &[u8], utf8: bool) -> &'static str {
+    from_extension(name)
+        .or_else(|| sniff(head))
+        .unwrap_or(if utf8 {
+            "text/plain"
+        } else {
+            "application/octet-stream"
+        })
+}
+
+/// Whether the decompressed `content` looks like what the extension of `name` promises: text
+/// for text types, the right magic for the binary types that have one. Unknown extensions
+/// are always plausible.
+pub fn plausible(name: &str, content: &[u8]) -> bool {
+    plausible_head(name, content, std::str::from_utf8(content).is_ok())
+}
+
+/// Like [`plausible`], for content only known by its first [`HEAD_LEN`] bytes and whether all
+/// of it is UTF-8.
+pub fn plausible_head(name: &str, head: &[u8], utf8: bool) -> bool {
+    match from_extension(name) {
+        Some(mime) if is_text(mime) => utf8,
+        Some(
+            mime @ ("image/png" | "image/jpeg" | "image/gif" | "image/webp" | "font/woff"
+            | "font/woff2" | "application/wasm" | "application/pdf"),
+        ) => sniff(head) == Some(mime),
+        _ => true,
+    }
+}
+
+/// Whether `mime` is a text type, including the structured ones (JSON, XML, SVG).
+pub fn is_text(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || matches!(
+            mime,
+            "application/json" | "application/xml" | "image/svg+xml"
+        )
+}
+
+fn from_extension(name: &str) -> Option<&'static str> {
+    let file_name = name.rsplit('/').next()?;
+    let (_, extension) = file_name.rsplit_once('.')?;
+    Some(match extension.to_ascii_lowercase().as_str() {
+        "html" | "htm" => "text/html",
+        "js" | "mjs" | "cjs" => "text/javascript",
+        "css" => "text/css",
+        "json" | "map" => "application/json",
+        "txt" => "text/plain",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "ico" => "image/vnd.microsoft.icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "wasm" => "application/wasm",
+        "pdf" => "application/pdf",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        _ => return None,
+    })
+}
+
+fn sniff(content: &[u8]) -> Option<&'static str> {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"\0\0\x01\0", "image/vnd.microsoft.icon"),
+        (b"wOFF", "font/woff"),
+        (b"wOF2", "font/woff2"),
+        (b"\0\x01\0\0", "font/ttf"),
+        (b"OTTO", "font/otf"),
+        (b"\0asm", "application/wasm"),
+        (b"%PDF-", "application/pdf"),
+    ];
+    if let Some((_, mime)) = MAGIC.iter().find(|(magic, _)| content.starts_with(magic)) {
+        return Some(mime);
+    }
+    if content.starts_with(b"RIFF") && content.get(8..12) == Some(b"WEBP") {
+        return Some("image/webp");
+    }
+
+    // markup, ignoring leading whitespace and case
+    let head = content.trim_ascii_start();
+    let head = &head[..head.len().min(64)];
+    let starts_with = |prefix: &[u8]| {
+        head.len() >= prefix.len() && head[..prefix.len()].eq_ignore_ascii_case(prefix)
+    };
+    if starts_with(b"<!doctype html") || starts_with(b"<html") {
+        return Some("text/html");
+    }
+    if starts_with(b"<svg") {
+        return Some("image/svg+xml");
+    }
+    if starts_with(b"<?xml") {
+        return Some("application/xml");
+    }
+    None
+}
@@ -0,0 +1,169 @@
+use std::fmt;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use crate::codec::DEFAULT_BROTLI_BUFFER_SIZE;
+use crate::Codec;
+
+/// Default for [`ScanOptions::max_asset_size`], 256 MiB.
+pub const DEFAULT_MAX_ASSET_SIZE: u64 = 256 * 1024 * 1024;
+
+/// How the assets are laid out in the binary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AssetFormat {
+    /// The table of name and data pointers `tauri-codegen` generates.
+    Tauri,
+    /// An Electron-style ASAR archive embedded as one blob.
+    Asar,
+}
+
+/// Which content [`Dumper::extract_to_map`](crate::Dumper::extract_to_map) keeps for a name
+/// several assets share.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameCollision {
+    /// The asset the scan finds first.
+    #[default]
+    KeepFirst,
+    /// The asset the scan finds last.
+    KeepLast,
+    /// Fail with [`DumperError::NameCollision`](crate::DumperError::NameCollision).
+    Error,
+}
+
+/// Order of the four pointer-sized fields of an asset table entry.
+///
+/// Rust leaves the layout of tuples and slices unspecified, so a compiler release may lay the
+/// `(&str, &[u8])` entries out differently. The default is `name ptr, name len, data ptr, data
+/// len`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeaderLayout {
+    /// The data slice comes before the name.
+    pub data_first: bool,
+    /// Each slice holds its length before its pointer.
+    pub len_first: bool,
+}
+
+impl HeaderLayout {
+    /// Every layout, the default first.
+    pub const ALL: [HeaderLayout; 4] = [
+        HeaderLayout::new(false, false),
+        HeaderLayout::new(true, false),
+        HeaderLayout::new(false, true),
+        HeaderLayout::new(true, true),
+    ];
+
+    const fn new(data_first: bool, len_first: bool) -> Self {
+        Self {
+            data_first,
+            len_first,
+        }
+    }
+
+    // index of each field in the entry
+    pub(crate) fn name_ptr(self) -> usize {
+        self.slice(!self.data_first, false)
+    }
+
+    pub(crate) fn name_len(self) -> usize {
+        self.slice(!self.data_first, true)
+    }
+
+    pub(crate) fn data_ptr(self) -> usize {
+        self.slice(self.data_first, false)
+    }
+
+    pub(crate) fn data_len(self) -> usize {
+        self.slice(self.data_first, true)
+    }
+
+    fn slice(self, first: bool, len: bool) -> usize {
+        (if first { 0 } else { 2 }) + usize::from(len != self.len_first)
+    }
+}
+
+impl fmt::Display for HeaderLayout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fields = [""; 4];
+        fields[self.name_ptr()] = "name ptr";
+        fields[self.name_len()] = "name len";
+        fields[self.data_ptr()] = "data ptr";
+        fields[self.data_len()] = "data len";
+        f.write_str(&fields.join(", "))
+    }
+}
+
+/// Knobs for the asset scan, see [`Dumper::set_options`](crate::Dumper::set_options).
+#[derive(Debug, Clone)]
+pub struct ScanOptions {
+    /// Only accept assets encoded with this codec instead of auto-detecting it.
+    pub codec: Option<Codec>,
+    /// Only look for assets laid out this way; by default the asset table is scanned for,
+    /// with PE resources and ASAR archives as fallbacks.
+    pub format: Option<AssetFormat>,
+    /// Field order of the asset table entries; by default it is detected by
+    /// [`Dumper::heuristic_search_assets`](crate::Dumper::heuristic_search_assets) when the
+    /// usual order finds nothing.
+    pub layout: Option<HeaderLayout>,
+    /// Codecs auto-detection tries, in order; add a [`Codec::Custom`] to support another
+    /// scheme.
+    pub codecs: Vec<Codec>,
+    /// Scan exactly the section with this name, optionally qualified by the segment
+    /// (`__DATA_CONST,__const`), instead of picking the candidate sections.
+    pub section: Option<String>,
+    /// File offset to start scanning at instead of the start of the candidate sections.
+    pub scan_start: Option<u64>,
+    /// Number of bytes to scan; defaults to the rest of the file when only a start is given.
+    pub scan_len: Option<u64>,
+    /// Candidates declaring more embedded bytes than this are rejected without decoding them.
+    pub max_asset_size: u64,
+    /// Size of the buffer brotli decodes into, independent of the asset sizes.
+    pub brotli_buffer_size: usize,
+    /// Custom dictionary the brotli streams were compressed against (`brotli -D`), used
+    /// both to validate candidates and to decompress them.
+    pub brotli_dictionary: Option<Vec<u8>>,
+    /// Decode the data of every candidate to make sure it is an asset. Without it only the
+    /// pointers, the name and the codec header are checked: much faster, but false
+    /// positives slip through and the codec is a guess.
+    pub validate_data: bool,
+    /// Content decoded while validating a candidate is kept in
+    /// [`Asset::decompressed`](crate::Asset::decompressed) up to this many bytes, everything
+    /// by default. Larger content is decoded again by
+    /// [`Dumper::decompress_asset_to`](crate::Dumper::decompress_asset_to), so a dump
+    /// streaming the assets to files never holds more than a few of them in memory.
+    pub max_kept_decoded: usize,
+    /// Locate the table as the longest run of at least this many consecutive entries that
+    /// point at plausible names and data, then decode exactly that run: faster, and matches
+    /// elsewhere are ignored. Without such a run every offset is scanned as usual. Only
+    /// [`Dumper::par_search_assets`](crate::Dumper::par_search_assets) looks for runs, not
+    /// the [`Dumper::assets`](crate::Dumper::assets) iterator.
+    pub min_run: Option<usize>,
+    /// What every asset table name starts with, `/` by default; `tauri://localhost/` for
+    /// builds naming the assets by URL. Empty accepts any name that looks like a path,
+    /// rooted or relative.
+    pub name_prefix: String,
+    /// Aborts the scan once set, e.g. from a UI thread or a timer: it stops within a chunk
+    /// and fails with [`DumperError::Cancelled`](crate::DumperError::Cancelled).
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            codec: None,
+            format: None,
+            layout: None,
+            codecs: Codec::BUILTIN.to_vec(),
+            section: None,
+            scan_start: None,
+            scan_len: None,
+            max_asset_size: DEFAULT_MAX_ASSET_SIZE,
+            brotli_buffer_size: DEFAULT_BROTLI_BUFFER_SIZE,
+            brotli_dictionary: None,
+            validate_data: true,
+            max_kept_decoded: usize::MAX,
+            min_run: None,
+            name_prefix: "/".to_string(),
+            cancel: None,
+        }
+    }
+}
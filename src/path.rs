@@ -0,0 +1,137 @@
+use std::borrow::Cow;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::{DumperError, Result};
+
+/// Turns an asset name like `/assets/app.js` into a path relative to the output directory.
+///
+/// Names with a `..` component, a drive or UNC prefix, or a NUL byte are refused, whatever
+/// the separators used. Leading slashes never re-root the path.
+pub fn relative_path(name: &str) -> Result<PathBuf> {
+    let relative = name.trim_start_matches(['/', '\\']);
+    let path = Path::new(relative);
+
+    if relative.contains('\0') {
+        return Err(DumperError::Nul(name.to_string()));
+    }
+    if relative.split(['/', '\\']).any(|c| c == "..") {
+        return Err(DumperError::PathTraversal(name.to_string()));
+    }
+    if !path
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(DumperError::PathTraversal(name.to_string()));
+    }
+
+    Ok(path.to_path_buf())
+}
+
+/// Checks that the existing directory `dir` resolves inside `root`, following symlinks.
+///
+/// `root` must already be canonical.
+pub fn ensure_within(root: &Path, dir: &Path) -> Result<()> {
+    let dir = dir
+        .canonicalize()
+        .map_err(|e| io::Error::new(e.kind(), format!("failed to resolve {:?}: {}", dir, e)))?;
+    if !dir.starts_with(root) {
+        return Err(DumperError::OutsideOutput(dir));
+    }
+    Ok(())
+}
+
+/// Rewrites an asset name to the file a static web server would serve it from.
+///
+/// Backslashes become slashes, query strings and fragments are dropped, and directory
+/// names get an `index.html`.
+pub fn dist_name(name: &str) -> String {
+    let name = name.replace('\\', "/");
+    let name = name.split(['?', '#']).next().unwrap_or_default();
+
+    if name.is_empty() || name.ends_with('/') {
+        format!("{}index.html", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Characters Windows refuses in file names, besides control characters.
+pub const WINDOWS_INVALID_CHARS: [char; 7] = ['<', '>', ':', '"', '|', '?', '*'];
+
+// device names, reserved with any extension
+const WINDOWS_DEVICE_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Makes every component of the relative `path` a valid Windows file name.
+///
+/// Forbidden and control characters, as well as trailing dots and spaces, become
+/// `replacement`; device names like `CON` or `nul.txt` are prefixed with it.
+pub fn portable_path(path: &Path, replacement: &str) -> PathBuf {
+    path.components()
+        .map(|component| match component {
+            Component::Normal(name) => portable_name(&name.to_string_lossy(), replacement),
+            other => other.as_os_str().to_string_lossy().into_owned(),
+        })
+        .collect()
+}
+
+fn portable_name(name: &str, replacement: &str) -> String {
+    let kept = name.trim_end_matches(['.', ' ']);
+    let mut portable = String::with_capacity(name.len());
+    for c in kept.chars() {
+        if c.is_control() || WINDOWS_INVALID_CHARS.contains(&c) {
+            portable.push_str(replacement);
+        } else {
+            portable.push(c);
+        }
+    }
+    for _ in kept.len()..name.len() {
+        portable.push_str(replacement);
+    }
+
+    let stem = portable.split('.').next().unwrap_or_default();
+    if WINDOWS_DEVICE_NAMES
+        .iter()
+        .any(|device| stem.trim_end().eq_ignore_ascii_case(device))
+    {
+        // with nothing to replace them with, device names still need to change
+        portable.insert_str(
+            0,
+            if replacement.is_empty() {
+                "_"
+            } else {
+                replacement
+            },
+        );
+    }
+    portable
+}
+
+/// On Windows, prefixes absolute paths too long for `MAX_PATH` (260 characters) with `\\?\`
+/// so they can still be written; anything else is returned as is.
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    const MAX_PATH: usize = 260;
+
+    if !cfg!(windows) || !path.is_absolute() {
+        return Cow::Borrowed(path);
+    }
+    let text = path.to_string_lossy();
+    // `/` separates nothing in a verbatim path, e.g. a canonical `\\?\C:\out` joined with
+    // `assets/app.js`
+    let long = if let Some(verbatim) = text.strip_prefix(r"\\?\") {
+        if !verbatim.contains('/') {
+            return Cow::Borrowed(path);
+        }
+        text.replace('/', r"\")
+    } else if text.len() < MAX_PATH {
+        return Cow::Borrowed(path);
+    } else if let Some(share) = text.strip_prefix(r"\\") {
+        format!(r"\\?\UNC\{}", share.replace('/', r"\"))
+    } else {
+        format!(r"\\?\{}", text.replace('/', r"\"))
+    };
+    Cow::Owned(PathBuf::from(long))
+}
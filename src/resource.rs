@@ -0,0 +1,106 @@
+use anyhow::Result;
+use object::pe;
+use object::read::pe::{
+    ImageNtHeaders, PeFile, ResourceDirectory, ResourceDirectoryEntryData, ResourceNameOrId,
+};
+use object::LittleEndian as LE;
+
+// a leaf of the PE resource tree that may hold an asset
+#[derive(Debug)]
+pub(crate) struct Resource {
+    pub name: String,
+    pub offset: usize,
+    pub size: usize,
+}
+
+/// Walks the resource directory (`.rsrc`) of a PE binary, yielding the data resources.
+pub(crate) fn pe_resources(data: &[u8], is_64: bool) -> Result<Vec<Resource>> {
+    if is_64 {
+        collect::<pe::ImageNtHeaders64>(data)
+    } else {
+        collect::<pe::ImageNtHeaders32>(data)
+    }
+}
+
+fn collect<Pe: ImageNtHeaders>(data: &[u8]) -> Result<Vec<Resource>> {
+    let file = PeFile::<Pe>::parse(data)?;
+    let sections = file.section_table();
+    let Some(directory) = file
+        .data_directories()
+        .resource_directory(data, &sections)?
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut resources = Vec::new();
+    // type, then name, then language
+    for type_entry in directory.root()?.entries {
+        let kind = type_entry.name_or_id();
+        if !is_data_type(&kind) {
+            continue;
+        }
+        let Some(names) = type_entry.data(directory)?.table() else {
+            continue;
+        };
+        for name_entry in names.entries {
+            let name = resource_name(directory, &kind, name_entry.name_or_id())?;
+            // translations of one resource share its name, the first one stands for all
+            let leaf = match name_entry.data(directory)? {
+                ResourceDirectoryEntryData::Table(languages) => match languages.entries.first() {
+                    Some(language) => language.data(directory)?.data(),
+                    None => None,
+                },
+                ResourceDirectoryEntryData::Data(leaf) => Some(leaf),
+            };
+            let Some(leaf) = leaf else {
+                continue;
+            };
+
+            let (rva, size) = (leaf.offset_to_data.get(LE), leaf.size.get(LE));
+            match sections.pe_file_range_at(rva) {
+                Some((offset, available)) if size <= available => resources.push(Resource {
+                    name,
+                    offset: offset as usize,
+                    size: size as usize,
+                }),
+                _ => log::debug!(
+                    "resource {}: data {:#x}+{:#x} is out of range",
+                    name,
+                    rva,
+                    size
+                ),
+            }
+        }
+    }
+    Ok(resources)
+}
+
+// frontends go in RCDATA, HTML or a custom named type; icons, manifests and the like do not
+fn is_data_type(kind: &ResourceNameOrId) -> bool {
+    match kind {
+        ResourceNameOrId::Name(_) => true,
+        ResourceNameOrId::Id(id) => matches!(*id, pe::RT_RCDATA | pe::RT_HTML),
+    }
+}
+
+// names are paths, `index.html` or `/assets/app.js`; numbered resources become `/RCDATA/101`
+fn resource_name(
+    directory: ResourceDirectory,
+    kind: &ResourceNameOrId,
+    name: ResourceNameOrId,
+) -> Result<String> {
+    match name {
+        ResourceNameOrId::Name(name) => {
+            let name = name.to_string_lossy(directory)?.replace('\\', "/");
+            Ok(format!("/{}", name.trim_start_matches('/')))
+        }
+        ResourceNameOrId::Id(id) => {
+            let kind = match kind {
+                ResourceNameOrId::Name(kind) => kind.to_string_lossy(directory)?,
+                ResourceNameOrId::Id(pe::RT_HTML) => "HTML".to_string(),
+                ResourceNameOrId::Id(_) => "RCDATA".to_string(),
+            };
+            Ok(format!("/{}/{}", kind, id))
+        }
+    }
+}
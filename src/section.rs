@@ -0,0 +1,30 @@
+use object::SectionKind;
+
+/// A section of the binary, as reported by [`Dumper::sections`](crate::Dumper::sections).
+#[derive(Debug, Clone)]
+pub struct Section {
+    pub name: String,
+    /// Segment the section belongs to, only known for Mach-O.
+    pub segment: Option<String>,
+    pub kind: SectionKind,
+    /// Virtual address of the section.
+    pub address: u64,
+    pub size: u64,
+    /// Offset and length of the section in the file, `None` for e.g. `.bss`.
+    pub file_range: Option<(u64, u64)>,
+}
+
+impl Section {
+    /// Name qualified by the segment when there is one, e.g. `__DATA_CONST,__const`.
+    pub fn full_name(&self) -> String {
+        match &self.segment {
+            Some(segment) => format!("{},{}", segment, self.name),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Whether `name` names this section, either bare or qualified by the segment.
+    pub fn is_named(&self, name: &str) -> bool {
+        self.name == name || self.segment.is_some() && self.full_name() == name
+    }
+}
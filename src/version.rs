@@ -0,0 +1,71 @@
+use memchr::memmem;
+use std::collections::HashMap;
+use std::fmt;
+
+// panic locations embed the source paths of the crates, version included:
+// `.../index.crates.io-6f17d22bba15001f/tauri-2.1.1/src/app.rs`
+const CRATE_PATH: &[u8] = b"tauri-";
+
+// the JavaScript glue injected into the webview names its globals after the major version
+const GLOBALS: [(&[u8], u64); 2] = [(b"__TAURI_INTERNALS__", 2), (b"__TAURI_IPC__", 1)];
+
+/// The Tauri release a binary was built with, as far as the strings embedded in it tell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TauriVersion {
+    pub major: u64,
+    /// Version of the `tauri` crate, when its source paths are embedded.
+    pub exact: Option<String>,
+}
+
+impl fmt::Display for TauriVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.exact {
+            Some(version) => write!(f, "v{}", version),
+            None => write!(f, "~v{}.x", self.major),
+        }
+    }
+}
+
+/// Looks for the source paths of the `tauri` crate, or failing that for the globals of its
+/// JavaScript glue, in `data`. Release builds usually keep both.
+pub(crate) fn tauri_version(data: &[u8]) -> Option<TauriVersion> {
+    // the version seen most often, dependencies may embed paths of another release
+    let mut versions: HashMap<&str, usize> = HashMap::new();
+    for found in memmem::find_iter(data, CRATE_PATH) {
+        if let Some(version) = crate_version(&data[found + CRATE_PATH.len()..]) {
+            *versions.entry(version).or_default() += 1;
+        }
+    }
+    let exact = versions
+        .into_iter()
+        .max_by_key(|&(version, count)| (count, version))
+        .map(|(version, _)| version);
+    if let Some(version) = exact {
+        let major = version.split('.').next()?.parse().ok()?;
+        return Some(TauriVersion {
+            major,
+            exact: Some(version.to_string()),
+        });
+    }
+
+    GLOBALS.iter().find_map(|&(global, major)| {
+        memmem::find(data, global).map(|_| TauriVersion { major, exact: None })
+    })
+}
+
+// `2.1.1` or `2.0.0-rc.3` from `2.1.1/src/...`: a semver version followed by a path
+// separator, anything else after `tauri-` is another crate such as `tauri-utils`
+fn crate_version(data: &[u8]) -> Option<&str> {
+    const MAX_LEN: usize = 32;
+
+    let end = data
+        .iter()
+        .take(MAX_LEN)
+        .position(|&b| b == b'/' || b == b'\\')?;
+    let version = std::str::from_utf8(&data[..end]).ok()?;
+    let (core, pre) = version.split_once('-').unwrap_or((version, ""));
+    let parts: Vec<_> = core.split('.').collect();
+    let numeric = |part: &str| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit());
+    let pre_ok = pre.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'.');
+    (parts.len() == 3 && parts.iter().all(|part| numeric(part)) && pre_ok).then_some(version)
+}
@@ -0,0 +1,257 @@
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use serde_json::Value;
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+// an empty directory of its own for a test, no config file is picked up from it
+fn scratch(name: &str) -> PathBuf {
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR"))
+        .join("cli")
+        .join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn tauri_dumper(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tauri-dumper"))
+        .args(args)
+        .current_dir(dir)
+        .env("NO_COLOR", "1")
+        .env("RUST_BACKTRACE", "0")
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+// every file under `dir`, relative to it
+fn files(dir: &Path) -> Vec<String> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(next) = dirs.pop() {
+        for entry in fs::read_dir(next).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else {
+                let relative = path.strip_prefix(dir).unwrap();
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn exit_codes() {
+    let dir = scratch("exit-codes");
+    let pe64 = fixture("pe64.exe");
+    let code = |args: &[&str]| tauri_dumper(&dir, args).status.code();
+
+    assert_eq!(code(&["-i", &pe64, "--list"]), Some(0));
+    assert_eq!(code(&["-i", &fixture("README.md"), "--list"]), Some(2));
+    assert_eq!(code(&["-i", &pe64, "--exclude", "**", "--list"]), Some(3));
+    assert_eq!(code(&["-i", "missing.exe", "--list"]), Some(4));
+
+    assert_eq!(
+        code(&["-i", &pe64, "-o", "out", "--manifest", "manifest.json"]),
+        Some(0)
+    );
+    let mut manifest: Value =
+        serde_json::from_slice(&fs::read(dir.join("manifest.json")).unwrap()).unwrap();
+    manifest["assets"][0]["sha256"] = Value::from("0".repeat(64));
+    fs::write(dir.join("changed.json"), manifest.to_string()).unwrap();
+    assert_eq!(code(&["-i", &pe64, "--verify", "manifest.json"]), Some(0));
+    assert_eq!(code(&["-i", &pe64, "--verify", "changed.json"]), Some(5));
+
+    // options that would be dropped are refused
+    assert_eq!(
+        code(&["-i", &pe64, "--count-only", "--report-json", "r.json"]),
+        Some(2)
+    );
+    assert_eq!(
+        code(&[
+            "-i",
+            &pe64,
+            "--baseline",
+            "manifest.json",
+            "--max-assets",
+            "1"
+        ]),
+        Some(2)
+    );
+}
+
+#[test]
+fn config_file() {
+    let dir = scratch("config-file");
+    fs::write(
+        dir.join("tauri-dumper.toml"),
+        format!(
+            "input = {:?}\nlist = true\nexclude = [\"assets/*\"]\n",
+            fixture("pe64.exe")
+        ),
+    )
+    .unwrap();
+    let output = tauri_dumper(&dir, &["-q"]);
+    assert!(output.status.success(), "{output:?}");
+    let listed = stdout(&output);
+    assert!(listed.contains("/index.html"), "{listed}");
+    assert!(!listed.contains("/assets/app.js"), "{listed}");
+
+    // the command line wins over the config
+    let output = tauri_dumper(&dir, &["-q", "--exclude", "index.html"]);
+    let listed = stdout(&output);
+    assert!(!listed.contains("/index.html"), "{listed}");
+    assert!(listed.contains("/assets/app.js"), "{listed}");
+
+    fs::write(dir.join("other.toml"), "no-such-option = true\n").unwrap();
+    let output = tauri_dumper(&dir, &["--config", "other.toml"]);
+    assert_eq!(output.status.code(), Some(1));
+    let error = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        error.contains("unknown option \"no-such-option\""),
+        "{error}"
+    );
+}
+
+#[test]
+fn event_stream() {
+    let dir = scratch("event-stream");
+    let output = tauri_dumper(
+        &dir,
+        &["-i", &fixture("pe64.exe"), "-o", "out", "--events", "json"],
+    );
+    assert!(output.status.success(), "{output:?}");
+    // nothing but events on stdout, one JSON object per line
+    let events: Vec<Value> = stdout(&output)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let kinds: Vec<_> = events
+        .iter()
+        .map(|e| e["event"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        kinds,
+        [
+            "scan_started",
+            "asset_found",
+            "asset_found",
+            "asset_found",
+            "asset_written",
+            "asset_written",
+            "asset_written",
+            "scan_complete"
+        ]
+    );
+    assert_eq!(events[0]["format"], "Pe");
+    assert_eq!(events[1]["name"], "/index.html");
+    assert_eq!(events[1]["codec"], "brotli");
+    let written: Vec<_> = events[4..7]
+        .iter()
+        .map(|e| e["path"].as_str().unwrap())
+        .collect();
+    // written in the --sort order, by name unless told otherwise
+    assert_eq!(written, ["assets/app.js", "assets/style.css", "index.html"]);
+    assert_eq!(events[7]["assets"], 3);
+
+    let output = tauri_dumper(&dir, &["-i", "missing.exe", "--list", "--events", "json"]);
+    let error: Value = serde_json::from_str(stdout(&output).trim()).unwrap();
+    assert_eq!(error["event"], "error");
+    assert_eq!(error["exit_code"], 4);
+}
+
+#[test]
+fn changed_only() {
+    let dir = scratch("changed-only");
+    let pe64 = fixture("pe64.exe");
+    let output = tauri_dumper(&dir, &["-i", &pe64, "-o", "old", "--manifest", "old.json"]);
+    assert!(output.status.success(), "{output:?}");
+
+    // as if /index.html had changed since, and /removed.js had been dropped
+    let mut manifest: Value =
+        serde_json::from_slice(&fs::read(dir.join("old.json")).unwrap()).unwrap();
+    let assets = manifest["assets"].as_array_mut().unwrap();
+    let index = assets
+        .iter()
+        .position(|a| a["name"] == "/index.html")
+        .unwrap();
+    assets[index]["sha256"] = Value::from("0".repeat(64));
+    let mut removed = assets[index].clone();
+    removed["name"] = Value::from("/removed.js");
+    assets.push(removed);
+    fs::write(dir.join("old.json"), manifest.to_string()).unwrap();
+
+    let output = tauri_dumper(
+        &dir,
+        &[
+            "-i",
+            &pe64,
+            "-o",
+            "new",
+            "--since",
+            "old.json",
+            "--changed-only",
+        ],
+    );
+    assert!(output.status.success(), "{output:?}");
+    assert_eq!(files(&dir.join("new")), ["index.html"]);
+    let report = stdout(&output);
+    assert!(report.contains("Modified: /index.html"), "{report}");
+    assert!(report.contains("Removed: /removed.js"), "{report}");
+    assert!(
+        report.contains("0 added, 1 removed, 1 modified, 2 unchanged"),
+        "{report}"
+    );
+}
+
+#[test]
+fn archives_are_reproducible() {
+    let dir = scratch("archives");
+    let pe64 = fixture("pe64.exe");
+    for run in ["first", "second"] {
+        let zip = format!("{run}.zip");
+        let tar = format!("{run}.tar.gz");
+        let output = tauri_dumper(&dir, &["-i", &pe64, "--zip", &zip, "--tar", &tar]);
+        assert!(output.status.success(), "{output:?}");
+    }
+    let read = |name: &str| fs::read(dir.join(name)).unwrap();
+    assert_eq!(read("first.zip"), read("second.zip"));
+    assert_eq!(read("first.tar.gz"), read("second.tar.gz"));
+
+    // no timestamps: the zip entries date from 1980, the tar ones and the gzip from 1970
+    let zip = read("first.zip");
+    assert_eq!(&zip[..4], b"PK\x03\x04");
+    assert_eq!(&zip[10..14], [0, 0, 0x21, 0]);
+    let tar = read("first.tar.gz");
+    assert_eq!(&tar[4..8], [0, 0, 0, 0]);
+    let mut unpacked = Vec::new();
+    flate2::read::GzDecoder::new(&tar[..])
+        .read_to_end(&mut unpacked)
+        .unwrap();
+    let mtimes: Vec<_> = unpacked
+        .chunks(512)
+        .filter(|header| header[257..262] == *b"ustar")
+        .map(|header| &header[136..147])
+        .collect();
+    assert!(!mtimes.is_empty());
+    assert!(
+        mtimes.iter().all(|mtime| mtime.iter().all(|&b| b == b'0')),
+        "{mtimes:?}"
+    );
+}
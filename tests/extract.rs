@@ -0,0 +1,796 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tauri_dumper::{
+    extract_assets, Architecture, AssetFormat, BinaryFormat, Codec, Decoder, DigestWriter, Dumper,
+    DumperError, HeaderLayout, Manifest, ManifestEntry, NameCollision, ScanOptions,
+};
+
+const INDEX_HTML: &[u8] = b"<html><body>hello tauri</body></html>\n\
+    <html><body>hello tauri</body></html>\n\
+    <html><body>hello tauri</body></html>\n";
+const APP_JS: &[u8] = b"console.log('hi');\nconsole.log('hi');\nconsole.log('hi');\n\
+    console.log('hi');\nconsole.log('hi');\n";
+const STYLE_CSS: &[u8] = b"body{color:red}\n";
+
+const ASSETS: [(&str, &[u8]); 3] = [
+    ("/index.html", INDEX_HTML),
+    ("/assets/app.js", APP_JS),
+    ("/assets/style.css", STYLE_CSS),
+];
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+// asserts the fixture holds exactly `expected`, in table order, encoded with `codec`
+fn assert_assets(name: &str, codec: Codec, expected: &[(&str, &[u8])]) {
+    let path = fixture(name);
+    let dumper = Dumper::open(&path).unwrap();
+    let assets = extract_assets(&path).unwrap();
+
+    let names: Vec<_> = assets.iter().map(|a| a.name.as_str()).collect();
+    let expected_names: Vec<_> = expected.iter().map(|(name, _)| *name).collect();
+    assert_eq!(names, expected_names, "{name}");
+
+    for (asset, (_, content)) in assets.iter().zip(expected) {
+        assert_eq!(asset.codec, codec, "{name}: {}", asset.name);
+        assert_eq!(asset.decompressed_len(), Some(content.len()), "{name}");
+        let decompressed = dumper.decompress_asset(asset).unwrap();
+        assert_eq!(decompressed, *content, "{name}: {}", asset.name);
+    }
+}
+
+#[test]
+fn pe64_brotli() {
+    assert_assets("pe64.exe", Codec::Brotli, &ASSETS);
+}
+
+#[test]
+fn pe64_gzip() {
+    assert_assets("pe64-gz.exe", Codec::Gzip, &ASSETS);
+}
+
+#[test]
+fn pe64_zstd() {
+    assert_assets("pe64-zst.exe", Codec::Zstd, &ASSETS);
+}
+
+#[test]
+fn pe32_brotli() {
+    assert_assets("pe32.exe", Codec::Brotli, &ASSETS);
+}
+
+#[test]
+fn pe64_name_outside_scanned_section() {
+    assert_assets("pe64-xsec.exe", Codec::Brotli, &ASSETS);
+}
+
+#[test]
+fn pe64_resources() {
+    let path = fixture("pe64-rsrc.exe");
+    let dumper = Dumper::open(&path).unwrap();
+    let assets = extract_assets(&path).unwrap();
+
+    let found: Vec<_> = assets
+        .iter()
+        .map(|a| {
+            (
+                a.name.as_str(),
+                a.codec,
+                dumper.decompress_asset(a).unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("/index.html", Codec::Brotli, INDEX_HTML.to_vec()),
+            ("/assets/app.js", Codec::Brotli, APP_JS.to_vec()),
+            ("/RCDATA/101", Codec::Identity, STYLE_CSS.to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn macho_x64() {
+    assert_assets("macho-x64", Codec::Brotli, &ASSETS);
+}
+
+#[test]
+fn macho_arm64_chained_fixups() {
+    assert_assets("macho-arm64-cf", Codec::Brotli, &ASSETS);
+}
+
+#[test]
+fn corrupt_chained_fixups() {
+    let binary = std::fs::read(fixture("macho-arm64-cf")).unwrap();
+    // LC_DYLD_CHAINED_FIXUPS points at 80 bytes at 0x8000, any field of them may be garbage
+    for at in 0x8000..0x8000 + 80 {
+        for garbage in [&[0x00][..], &[0x01], &[0xFF; 8]] {
+            let mut data = binary.clone();
+            let end = (at + garbage.len()).min(0x8000 + 80);
+            data[at..end].copy_from_slice(&garbage[..end - at]);
+            if let Ok(dumper) = Dumper::from_bytes(data) {
+                let _ = dumper.heuristic_search_assets();
+            }
+        }
+    }
+}
+
+#[test]
+fn macho_arm64e_authenticated_pointers() {
+    assert_assets("macho-arm64e-cf", Codec::Brotli, &ASSETS);
+}
+
+#[test]
+fn compressed_inputs() {
+    assert_assets("pe64.exe.gz", Codec::Brotli, &ASSETS);
+    assert_assets("macho-x64.xz", Codec::Brotli, &ASSETS);
+}
+
+#[test]
+fn elf_big_endian() {
+    assert_assets(
+        "ppc64-be",
+        Codec::Gzip,
+        &[
+            ("/index.html", b"<html><body>hi from ppc64</body></html>"),
+            ("/app.js", b"console.log('be');"),
+        ],
+    );
+}
+
+#[test]
+fn header_layout_is_detected() {
+    const EXPECTED: [(&str, &[u8]); 2] = [
+        ("/index.html", b"<html><body>data first</body></html>"),
+        ("/app.js", b"console.log('swapped');"),
+    ];
+    assert_assets("elf-data-first", Codec::Gzip, &EXPECTED);
+
+    let dumper = Dumper::open(fixture("elf-data-first")).unwrap();
+    assert_eq!(dumper.header_layout(), HeaderLayout::default());
+    dumper.heuristic_search_assets().unwrap();
+    let layout = dumper.header_layout();
+    assert_eq!(
+        layout,
+        HeaderLayout {
+            data_first: true,
+            len_first: false
+        }
+    );
+    assert_eq!(layout.to_string(), "data ptr, data len, name ptr, name len");
+    // later scans and lookups stick to it
+    assert!(dumper.find_asset("/app.js").unwrap().is_some());
+
+    let pe64 = Dumper::open(fixture("pe64.exe")).unwrap();
+    pe64.heuristic_search_assets().unwrap();
+    assert_eq!(pe64.header_layout(), HeaderLayout::default());
+}
+
+#[test]
+fn padded_table_entries() {
+    let dumper = Dumper::open(fixture("elf-padded")).unwrap();
+    let names = |assets: Vec<tauri_dumper::Asset>| {
+        let mut names: Vec<_> = assets.into_iter().map(|a| a.name).collect();
+        names.sort();
+        names
+    };
+    let expected = ["/app.css", "/app.js", "/index.html"];
+    assert_eq!(names(dumper.heuristic_search_assets().unwrap()), expected);
+    // the iterator steps from entry to entry rather than scanning every offset
+    let iterated = dumper.assets().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(names(iterated), expected);
+}
+
+#[test]
+fn brotli_dictionary() {
+    let mut dumper = Dumper::open(fixture("elf-brotli-dict")).unwrap();
+    // without the dictionary the streams do not decode, leaving only raw garbage
+    let assets = dumper.heuristic_search_assets().unwrap();
+    assert!(assets.iter().all(|a| a.codec == Codec::Identity));
+
+    dumper.set_options(ScanOptions {
+        brotli_dictionary: Some(std::fs::read(fixture("brotli.dict")).unwrap()),
+        ..Default::default()
+    });
+    let assets = dumper.heuristic_search_assets().unwrap();
+    let js = assets.iter().find(|a| a.name == "/app.js").unwrap();
+    assert_eq!(js.codec, Codec::Brotli);
+    assert_eq!(
+        dumper.decompress_asset(js).unwrap(),
+        b"document.addEventListener('DOMContentLoaded', () => console.log('dictionary'));"
+    );
+}
+
+#[test]
+fn scan_progress() {
+    let dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    let mut reports = Vec::new();
+    let assets = dumper
+        .scan_with_progress(|scanned, total| reports.push((scanned, total)))
+        .unwrap();
+    assert_eq!(assets.len(), ASSETS.len());
+    let &(scanned, total) = reports.last().unwrap();
+    assert_eq!(scanned, total);
+    assert!(reports.windows(2).all(|w| w[0].0 < w[1].0), "{reports:?}");
+}
+
+#[test]
+fn cancelled_scan() {
+    let mut dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    let cancel = Arc::new(AtomicBool::new(true));
+    dumper.set_options(ScanOptions {
+        cancel: Some(cancel.clone()),
+        ..Default::default()
+    });
+    assert!(matches!(
+        dumper.heuristic_search_assets(),
+        Err(DumperError::Cancelled)
+    ));
+    let mut assets = dumper.assets();
+    assert!(matches!(assets.next(), Some(Err(DumperError::Cancelled))));
+    assert!(assets.next().is_none());
+
+    cancel.store(false, Ordering::Relaxed);
+    assert_eq!(
+        dumper.heuristic_search_assets().unwrap().len(),
+        ASSETS.len()
+    );
+}
+
+#[test]
+fn name_prefix() {
+    let mut dumper = Dumper::open(fixture("elf-url-names")).unwrap();
+    assert!(dumper.heuristic_search_assets().unwrap().is_empty());
+
+    let expected = [
+        "tauri://localhost/index.html",
+        "tauri://localhost/assets/app.js",
+    ];
+    for prefix in ["tauri://localhost/", ""] {
+        dumper.set_options(ScanOptions {
+            name_prefix: prefix.to_string(),
+            ..Default::default()
+        });
+        let assets = dumper.heuristic_search_assets().unwrap();
+        let names: Vec<_> = assets.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, expected, "prefix {prefix:?}");
+    }
+}
+
+#[test]
+fn split_asset_tables() {
+    let dumper = Dumper::open(fixture("elf-two-tables")).unwrap();
+    let expected = [
+        "/index.html",
+        "/main.js",
+        "/main.css",
+        "/plugin/index.html",
+        "/plugin/plugin.js",
+        "/plugin/plugin.css",
+    ];
+    let assets = dumper.heuristic_search_assets().unwrap();
+    let names: Vec<_> = assets.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, expected);
+    let iterated: Vec<_> = dumper.assets().map(|a| a.unwrap().name).collect();
+    assert_eq!(iterated, expected);
+    assert_eq!(
+        dumper.count_tables(assets.iter().filter_map(|a| a.table_entry.as_ref())),
+        2
+    );
+
+    let dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    let assets = dumper.heuristic_search_assets().unwrap();
+    assert_eq!(
+        dumper.count_tables(assets.iter().filter_map(|a| a.table_entry.as_ref())),
+        1
+    );
+}
+
+#[test]
+fn manifest_errors_have_kinds() {
+    assert!(matches!(
+        Manifest::read(fixture("missing.json")),
+        Err(DumperError::Io(_))
+    ));
+    assert!(matches!(
+        Manifest::read(fixture("README.md")),
+        Err(DumperError::Json(_))
+    ));
+}
+
+#[test]
+fn extracted_to_map() {
+    let dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    let contents = dumper.extract_to_map(NameCollision::Error).unwrap();
+    assert_eq!(contents.len(), ASSETS.len());
+    for (name, content) in ASSETS {
+        assert_eq!(contents[name], content, "{name}");
+    }
+
+    // the second entry named after the first, in place of `/assets/app.js`
+    let mut binary = std::fs::read(fixture("pe64.exe")).unwrap();
+    let entries: Vec<_> = dumper
+        .assets()
+        .map(|a| a.unwrap().table_entry.unwrap().offset as usize)
+        .collect();
+    binary.copy_within(entries[0]..entries[0] + 16, entries[1]);
+    let dumper = Dumper::from_bytes(binary).unwrap();
+    let first = dumper.extract_to_map(NameCollision::KeepFirst).unwrap();
+    assert_eq!(first["/index.html"], INDEX_HTML);
+    let last = dumper.extract_to_map(NameCollision::KeepLast).unwrap();
+    assert_eq!(last["/index.html"], APP_JS);
+    assert!(!last.contains_key("/assets/app.js"));
+    assert!(matches!(
+        dumper.extract_to_map(NameCollision::Error),
+        Err(DumperError::NameCollision(name)) if name == "/index.html"
+    ));
+}
+
+#[test]
+fn streamed_decompression() {
+    for name in ["pe64.exe", "pe64-gz.exe", "pe64-zst.exe"] {
+        let mut dumper = Dumper::open(fixture(name)).unwrap();
+        // no content kept from the scan, so it is decoded while written
+        dumper.set_options(ScanOptions {
+            validate_data: false,
+            ..Default::default()
+        });
+        for asset in dumper.heuristic_search_assets().unwrap() {
+            let mut digest = DigestWriter::new(Vec::new());
+            dumper.decompress_asset_to(&asset, &mut digest).unwrap();
+            let streamed = ManifestEntry::from_digest(&asset, &digest);
+            let content = digest.into_inner();
+            assert_eq!(content, dumper.decompress_asset(&asset).unwrap(), "{name}");
+
+            let entry = ManifestEntry::new(&asset, &content);
+            assert_eq!(streamed.sha256, entry.sha256);
+            assert_eq!(streamed.mime, entry.mime);
+            assert_eq!(streamed.decompressed_size, entry.decompressed_size);
+        }
+    }
+}
+
+#[test]
+fn validated_content_past_the_limit_not_kept() {
+    for name in ["pe64.exe", "pe64-gz.exe", "pe64-zst.exe"] {
+        let kept = Dumper::open(fixture(name)).unwrap();
+        let mut dumper = Dumper::open(fixture(name)).unwrap();
+        // as the CLI scans when streaming: validated, but only small content kept
+        dumper.set_options(ScanOptions {
+            max_kept_decoded: 16,
+            ..Default::default()
+        });
+        let assets = dumper.heuristic_search_assets().unwrap();
+        let all = kept.heuristic_search_assets().unwrap();
+        assert_eq!(assets.len(), all.len(), "{name}");
+        for (asset, full) in assets.iter().zip(&all) {
+            assert_eq!(asset.name, full.name);
+            assert_eq!(asset.codec, full.codec);
+            match (&asset.decompressed, &full.decompressed) {
+                (Some(small), Some(content)) => {
+                    assert!(small.len() <= 16);
+                    assert_eq!(small, content);
+                }
+                (None, Some(content)) => assert!(content.len() > 16),
+                (kept, content) => assert_eq!(kept, content),
+            }
+            let mut content = Vec::new();
+            dumper.decompress_asset_to(asset, &mut content).unwrap();
+            assert_eq!(content, kept.decompress_asset(full).unwrap(), "{name}");
+        }
+        assert!(assets.iter().any(|asset| asset.decompressed.is_none()));
+    }
+}
+
+#[test]
+fn asset_names_stay_in_the_output_directory() {
+    use tauri_dumper::path::relative_path;
+
+    for name in ["/../../x", "..\\x", "a/../../x", "a/..\\..\\x"] {
+        assert!(
+            matches!(relative_path(name), Err(DumperError::PathTraversal(_))),
+            "{name:?}"
+        );
+    }
+    assert!(matches!(relative_path("a\0b"), Err(DumperError::Nul(_))));
+    // prefixes only mean something to Windows, elsewhere they are plain names
+    for name in ["C:\\x", "\\\\server\\share"] {
+        let path = relative_path(name);
+        assert_eq!(path.is_ok(), !cfg!(windows), "{name:?}");
+        if let Ok(path) = path {
+            assert_eq!(path.components().count(), 1, "{name:?}");
+        }
+    }
+    assert_eq!(
+        relative_path("/assets/app.js").unwrap(),
+        Path::new("assets/app.js")
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn symlinked_directories_leave_the_output() {
+    use tauri_dumper::path::ensure_within;
+
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("symlinked-output");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("out/assets")).unwrap();
+    std::fs::create_dir_all(dir.join("elsewhere")).unwrap();
+    std::os::unix::fs::symlink(dir.join("elsewhere"), dir.join("out/linked")).unwrap();
+    let root = dir.join("out").canonicalize().unwrap();
+
+    ensure_within(&root, &root.join("assets")).unwrap();
+    assert!(matches!(
+        ensure_within(&root, &root.join("linked")),
+        Err(DumperError::OutsideOutput(_))
+    ));
+    assert!(matches!(
+        ensure_within(&root, &root.join("linked/..")),
+        Err(DumperError::OutsideOutput(_))
+    ));
+    assert!(matches!(
+        ensure_within(&root, &root.join("missing")),
+        Err(DumperError::Io(_))
+    ));
+}
+
+#[test]
+fn tauri_version() {
+    let binary = std::fs::read(fixture("pe64.exe")).unwrap();
+    assert_eq!(
+        Dumper::from_bytes(binary.clone()).unwrap().tauri_version(),
+        None
+    );
+
+    // strings appended past the sections, as the file is all that is searched
+    let with = |strings: &[&[u8]]| {
+        let mut data = binary.clone();
+        for string in strings {
+            data.extend_from_slice(string);
+        }
+        Dumper::from_bytes(data).unwrap().tauri_version().unwrap()
+    };
+    let version = with(&[
+        b"/registry/src/index.crates.io-6f17d22bba15001f/tauri-utils-2.1.0/src/config.rs\0",
+        b"C:\\cargo\\registry\\src\\tauri-2.1.1\\src\\app.rs\0",
+        b"__TAURI_INTERNALS__",
+    ]);
+    assert_eq!(version.major, 2);
+    assert_eq!(version.to_string(), "v2.1.1");
+    assert_eq!(with(&[b"window.__TAURI_IPC__"]).to_string(), "~v1.x");
+}
+
+#[test]
+fn table_found_as_run_of_entries() {
+    let mut dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    for min_run in [3, 4] {
+        dumper.set_options(ScanOptions {
+            min_run: Some(min_run),
+            ..Default::default()
+        });
+        // a run too short for `min_run` leaves the usual scan to find the assets
+        let assets = dumper.heuristic_search_assets().unwrap();
+        let names: Vec<_> = assets.iter().map(|a| a.name.as_str()).collect();
+        let expected: Vec<_> = ASSETS.iter().map(|(name, _)| *name).collect();
+        assert_eq!(names, expected, "min_run {min_run}");
+    }
+
+    // wiping the ends of the second table leaves `/plugin/plugin.js` as a lone entry
+    let mut binary = std::fs::read(fixture("elf-two-tables")).unwrap();
+    for asset in Dumper::from_bytes(binary.clone())
+        .unwrap()
+        .heuristic_search_assets()
+        .unwrap()
+    {
+        if matches!(
+            asset.name.as_str(),
+            "/plugin/index.html" | "/plugin/plugin.css"
+        ) {
+            let offset = asset.table_entry.unwrap().offset as usize;
+            binary[offset..offset + 32].fill(0);
+        }
+    }
+    let mut dumper = Dumper::from_bytes(binary).unwrap();
+    let first = ["/index.html", "/main.js", "/main.css"];
+    for (min_run, expected) in [
+        (None, &[&first[..], &["/plugin/plugin.js"]].concat()),
+        (Some(3), &first.to_vec()),
+        (Some(4), &[&first[..], &["/plugin/plugin.js"]].concat()),
+    ] {
+        dumper.set_options(ScanOptions {
+            min_run,
+            ..Default::default()
+        });
+        let assets = dumper.heuristic_search_assets().unwrap();
+        let names: Vec<_> = assets.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(&names, expected, "min_run {min_run:?}");
+    }
+}
+
+#[test]
+fn asar_archive() {
+    // directory entries come sorted by name
+    assert_assets(
+        "elf-asar",
+        Codec::Identity,
+        &[ASSETS[1], ASSETS[2], ASSETS[0]],
+    );
+
+    let mut dumper = Dumper::open(fixture("elf-asar")).unwrap();
+    dumper.set_options(ScanOptions {
+        format: Some(AssetFormat::Tauri),
+        ..Default::default()
+    });
+    assert!(dumper.heuristic_search_assets().unwrap().is_empty());
+}
+
+#[test]
+fn forced_binary_format() {
+    let file = || std::fs::File::open(fixture("macho-x64")).unwrap();
+    let dumper = Dumper::with_format(file(), BinaryFormat::MachO, None).unwrap();
+    assert_eq!(
+        dumper.heuristic_search_assets().unwrap().len(),
+        ASSETS.len()
+    );
+
+    assert!(matches!(
+        Dumper::with_format(file(), BinaryFormat::Pe, None),
+        Err(DumperError::UnsupportedFormat(_))
+    ));
+    assert!(matches!(
+        Dumper::with_format(file(), BinaryFormat::Wasm, None),
+        Err(DumperError::UnsupportedFormat(reason)) if reason.contains("only as PE")
+    ));
+}
+
+#[test]
+fn memory_image() {
+    const IMAGE_BASE: u64 = 0x140000000;
+    let file = std::fs::read(fixture("pe64.exe")).unwrap();
+    let dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+
+    // lay the sections out the way the loader maps them
+    let mut image = vec![0; 0x5000];
+    for section in dumper.sections() {
+        let (offset, size) = section.file_range.unwrap();
+        let at = (section.address - IMAGE_BASE) as usize;
+        image[at..at + size as usize]
+            .copy_from_slice(&file[offset as usize..(offset + size) as usize]);
+    }
+    let image = Dumper::from_memory_image(image, IMAGE_BASE, Some(Architecture::X86_64)).unwrap();
+    assert_eq!(image.image_base(), Some(IMAGE_BASE));
+
+    let assets = image.heuristic_search_assets().unwrap();
+    let names: Vec<_> = assets.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ASSETS.map(|(name, _)| name));
+    for (asset, (_, content)) in assets.iter().zip(ASSETS) {
+        assert_eq!(
+            asset.table_entry.unwrap().data_address,
+            IMAGE_BASE + asset.data_offset
+        );
+        assert_eq!(image.decompress_asset(asset).unwrap(), content);
+    }
+}
+
+#[test]
+fn from_bytes_matches_open() {
+    let data = std::fs::read(fixture("pe64.exe")).unwrap();
+    let assets = Dumper::from_bytes(data)
+        .unwrap()
+        .heuristic_search_assets()
+        .unwrap();
+    let opened = extract_assets(&fixture("pe64.exe")).unwrap();
+
+    assert_eq!(assets.len(), opened.len());
+    for (a, b) in assets.iter().zip(&opened) {
+        assert_eq!(
+            (&a.name, &a.data, a.data_offset),
+            (&b.name, &b.data, b.data_offset)
+        );
+    }
+}
+
+#[test]
+fn forced_codec_rejects_other_encodings() {
+    let mut dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    dumper.set_options(ScanOptions {
+        codec: Some(Codec::Gzip),
+        ..Default::default()
+    });
+    assert!(dumper.heuristic_search_assets().unwrap().is_empty());
+}
+
+#[test]
+fn unvalidated_scan_finds_the_same_assets() {
+    let mut dumper = Dumper::open(fixture("macho-x64")).unwrap();
+    dumper.set_options(ScanOptions {
+        validate_data: false,
+        ..Default::default()
+    });
+    let assets = dumper.heuristic_search_assets().unwrap();
+    let names: Vec<_> = assets.iter().map(|a| a.name.as_str()).collect();
+    assert_eq!(names, ASSETS.map(|(name, _)| name));
+    assert!(assets.iter().all(|a| a.decompressed_len().is_none()));
+}
+
+#[test]
+fn unknown_section_lists_the_available_ones() {
+    let mut dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    dumper.set_options(ScanOptions {
+        section: Some(".nope".to_string()),
+        ..Default::default()
+    });
+    let error = dumper.heuristic_search_assets().unwrap_err();
+    let message = error.to_string();
+    assert!(message.contains(".nope not found"), "{message}");
+    assert!(message.contains(".rdata"), "{message}");
+
+    let DumperError::SectionNotFound { name, available } = error else {
+        panic!("{error:?}");
+    };
+    assert_eq!(name, ".nope");
+    assert!(available.iter().any(|s| s == ".rdata"), "{available:?}");
+}
+
+#[test]
+fn errors_tell_what_went_wrong() {
+    let error = Dumper::from_bytes(b"not an executable".to_vec())
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&error, DumperError::UnsupportedFormat(reason)
+            if reason.contains("Tauri binary") && reason.contains("6e 6f 74 20")),
+        "{error:?}"
+    );
+
+    let error = Dumper::open(fixture("missing.exe")).err().unwrap();
+    assert!(matches!(error, DumperError::Io(_)), "{error:?}");
+
+    let dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    let error = dumper.asset_at(0).unwrap_err();
+    assert!(
+        matches!(error, DumperError::InvalidAsset { offset: 0, .. }),
+        "{error:?}"
+    );
+
+    let error = Codec::Brotli.decode(b"\x1b\xff\xff").unwrap_err();
+    assert!(
+        matches!(
+            error,
+            DumperError::Decompression {
+                codec: Codec::Brotli,
+                ..
+            }
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn find_asset_by_name() {
+    let dumper = Dumper::open(fixture("macho-arm64-cf")).unwrap();
+
+    let asset = dumper.find_asset("/assets/app.js").unwrap().unwrap();
+    assert_eq!(dumper.decompress_asset(&asset).unwrap(), APP_JS);
+    assert!(dumper.find_asset("/missing.js").unwrap().is_none());
+}
+
+#[test]
+fn assets_know_their_table_entry() {
+    let dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    for asset in dumper.heuristic_search_assets().unwrap() {
+        let entry = asset.table_entry.unwrap();
+        let reparsed = dumper.asset_at(entry.offset as usize).unwrap();
+        assert_eq!(reparsed.name, asset.name);
+        assert_eq!(reparsed.table_entry, Some(entry));
+        // PE pointers are absolute, .rdata maps 0x140002000 to file offset 0x600
+        assert_eq!(
+            entry.data_address - 0x140000000 - 0x2000,
+            asset.data_offset - 0x600
+        );
+    }
+
+    let resources = extract_assets(&fixture("pe64-rsrc.exe")).unwrap();
+    assert!(resources.iter().all(|a| a.table_entry.is_none()));
+
+    // entries that are not assets say why
+    let mut dumper = Dumper::open(fixture("pe64.exe")).unwrap();
+    let entry = dumper.heuristic_search_assets().unwrap()[0]
+        .table_entry
+        .unwrap();
+    dumper.set_options(ScanOptions {
+        max_asset_size: 1,
+        ..Default::default()
+    });
+    let error = dumper.asset_at(entry.offset as usize).unwrap_err();
+    assert!(
+        matches!(&error, DumperError::InvalidAsset { reason, .. } if reason.contains("exceeds the maximum")),
+        "{error:?}"
+    );
+    dumper.set_options(ScanOptions {
+        codec: Some(Codec::Zstd),
+        ..Default::default()
+    });
+    let error = dumper.asset_at(entry.offset as usize).unwrap_err();
+    assert!(
+        matches!(&error, DumperError::InvalidAsset { reason, .. } if reason.contains("not valid zstd")),
+        "{error:?}"
+    );
+
+    // data pointing at the name, past which it runs
+    let mut binary = std::fs::read(fixture("pe64.exe")).unwrap();
+    let at = entry.offset as usize + 16;
+    binary[at..at + 8].copy_from_slice(&entry.name_address.to_le_bytes());
+    let dumper = Dumper::from_bytes(binary).unwrap();
+    let error = dumper.asset_at(entry.offset as usize).unwrap_err();
+    assert!(
+        matches!(&error, DumperError::InvalidAsset { reason, .. } if reason.contains("overlap")),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn truncated_binary_does_not_panic() {
+    let mut data = std::fs::read(fixture("pe64.exe")).unwrap();
+    // cut .rdata short, right after the start of the asset table
+    data.truncate(0x690);
+    let assets = Dumper::from_bytes(data)
+        .unwrap()
+        .heuristic_search_assets()
+        .unwrap();
+    assert!(assets.len() < ASSETS.len());
+}
+
+#[derive(Debug)]
+struct Gunzip;
+
+impl Decoder for Gunzip {
+    fn name(&self) -> &'static str {
+        "gunzip"
+    }
+
+    fn sniff(&self, input: &[u8]) -> bool {
+        input.starts_with(&[0x1F, 0x8B])
+    }
+
+    fn try_decode(
+        &self,
+        _input: &[u8],
+    ) -> Result<Vec<u8>, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(b"decoded".to_vec())
+    }
+}
+
+#[test]
+fn custom_codecs_are_tried_in_order() {
+    let mut dumper = Dumper::open(fixture("pe64-gz.exe")).unwrap();
+    let mut options = ScanOptions::default();
+    options.codecs.insert(0, Codec::Custom(&Gunzip));
+    dumper.set_options(options);
+
+    let assets = dumper.heuristic_search_assets().unwrap();
+    assert_eq!(assets.len(), ASSETS.len());
+    for asset in &assets {
+        assert_eq!(asset.codec, Codec::Custom(&Gunzip));
+        assert_eq!(asset.codec.to_string(), "gunzip");
+        assert_eq!(dumper.decompress_asset(asset).unwrap(), b"decoded");
+    }
+}
+
+#[test]
+fn names_are_made_portable() {
+    use tauri_dumper::path::portable_path;
+
+    let portable = |name: &str| portable_path(Path::new(name), "_");
+    assert_eq!(portable("assets/a:b?.js"), Path::new("assets/a_b_.js"));
+    assert_eq!(portable("con/nul.txt"), Path::new("_con/_nul.txt"));
+    assert_eq!(portable("notes. "), Path::new("notes__"));
+    assert_eq!(portable("console.js"), Path::new("console.js"));
+    assert_eq!(portable_path(Path::new("a*b"), ""), Path::new("ab"));
+}